@@ -3,23 +3,458 @@
 //! This module defines the structure of the Abstract Syntax Tree (AST) used to represent
 //! parsed code and provides evaluation functionality for the AST nodes.
 
+use crate::builtins;
+use crate::builtins::Builtin;
+use crate::builtins::ContextBuiltin;
 use crate::context::Context;
+use crate::context::ControlFlow;
+use crate::context::Function;
+use crate::context::Module;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
 use crate::token::Token;
 use crate::token::TokenType;
+use crate::visitor::Visitor;
 
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
 use std::rc::Rc;
 
+/// The integer type of the language, 32 bits wide unless the `int64` feature is enabled.
+/// Arithmetic wraps around at the bounds of this type.
+#[cfg(not(feature = "int64"))]
+pub type Integer = i32;
+/// The integer type of the language, 64 bits wide since the `int64` feature is enabled.
+/// Arithmetic wraps around at the bounds of this type.
+#[cfg(feature = "int64")]
+pub type Integer = i64;
+
+/// Identifies an array by the address of its shared elements. Operations walking nested arrays
+/// record the arrays they are inside of, since an array can contain itself, e.g. after
+/// `push(a, a)`, and walking it naively would never end.
+type ArrayPtr = *const RefCell<Vec<RuntimeValue>>;
+
+thread_local! {
+  /// The arrays being formatted by `Debug`, which has no argument to pass them down with.
+  static DEBUG_ENCLOSING: RefCell<Vec<ArrayPtr>> = const { RefCell::new(Vec::new()) };
+}
+
 /// Represents a value during runtime evaluation.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub enum RuntimeValue {
   /// An integer value.
-  INTEGER(i32),
+  INTEGER(Integer),
+  /// A floating-point value, written with a decimal point in the source, e.g. `3.14`.
+  FLOAT(f64),
   /// A string value.
   STRING(String),
   /// A null value.
   NULL,
   /// A boolean value.
   BOOL(bool),
+  /// An array value. Arrays are shared by reference, assigning an array to another variable
+  /// aliases it, and element assignment mutates it in place for every holder.
+  ARRAY(Rc<RefCell<Vec<RuntimeValue>>>),
+  /// A function value, obtained by referring to a function by name.
+  FUNCTION(Rc<Function>),
+  /// A module value, bound by an import statement to the name it was imported as.
+  MODULE(Rc<Module>),
+}
+
+/// A hashable copy of a value, identifying memoized calls by their arguments. Two keys are equal
+/// exactly when the values they were made from are equal.
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub enum ValueKey {
+  INTEGER(Integer),
+  /// The bits of a float, with `-0.0` stored as `0.0` since they are equal.
+  FLOAT(u64),
+  STRING(String),
+  NULL,
+  BOOL(bool),
+  ARRAY(Vec<ValueKey>),
+}
+
+impl RuntimeValue {
+  /// Converts the value to a hashable key. Arrays are converted by content, as they are when the
+  /// key is made.
+  ///
+  /// # Returns
+  ///
+  /// * `Some(ValueKey)` for values made only of numbers, strings, booleans, NULL and arrays,
+  ///   `None` for values holding a function or a module, which are only equal to themselves, a
+  ///   NaN float, which is equal to nothing, or an array containing itself, which has no finite
+  ///   key.
+  pub fn to_key(&self) -> Option<ValueKey> {
+    self.to_key_within(&mut Vec::new())
+  }
+
+  /// Converts the value to a hashable key, see `to_key`.
+  ///
+  /// # Arguments
+  ///
+  /// * `enclosing` - The arrays the value is nested in.
+  ///
+  /// # Returns
+  ///
+  /// * `Option<ValueKey>` - The key, or `None` if the value has none.
+  fn to_key_within(&self, enclosing: &mut Vec<ArrayPtr>) -> Option<ValueKey> {
+    match self {
+      RuntimeValue::INTEGER(val) => Some(ValueKey::INTEGER(*val)),
+      RuntimeValue::FLOAT(val) if val.is_nan() => None,
+      RuntimeValue::FLOAT(val) if *val == 0.0 => Some(ValueKey::FLOAT(0.0_f64.to_bits())),
+      RuntimeValue::FLOAT(val) => Some(ValueKey::FLOAT(val.to_bits())),
+      RuntimeValue::STRING(val) => Some(ValueKey::STRING(val.clone())),
+      RuntimeValue::NULL => Some(ValueKey::NULL),
+      RuntimeValue::BOOL(val) => Some(ValueKey::BOOL(*val)),
+      RuntimeValue::ARRAY(elements) if enclosing.contains(&Rc::as_ptr(elements)) => None,
+      RuntimeValue::ARRAY(elements) => {
+        enclosing.push(Rc::as_ptr(elements));
+        let key = elements
+          .borrow()
+          .iter()
+          .map(|element| element.to_key_within(enclosing))
+          .collect::<Option<Vec<ValueKey>>>()
+          .map(ValueKey::ARRAY);
+        enclosing.pop();
+        key
+      }
+      RuntimeValue::FUNCTION(_) | RuntimeValue::MODULE(_) => None,
+    }
+  }
+
+  /// Compares two values for ordering. This is the single definition of value ordering, used by
+  /// every operation that needs to order values.
+  /// Numbers are ordered by numeric value, integers being converted to floats when compared with
+  /// one, and strings lexicographically. Any other combination of values cannot be ordered, nor
+  /// can a NaN float.
+  ///
+  /// # Arguments
+  ///
+  /// * `other` - The value to compare with.
+  ///
+  /// # Returns
+  ///
+  /// * `Some(Ordering)` if the values can be ordered, or `None` if they cannot.
+  pub fn compare(&self, other: &RuntimeValue) -> Option<Ordering> {
+    match (self, other) {
+      (RuntimeValue::INTEGER(val1), RuntimeValue::INTEGER(val2)) => Some(val1.cmp(val2)),
+      (RuntimeValue::FLOAT(val1), RuntimeValue::FLOAT(val2)) => val1.partial_cmp(val2),
+      (RuntimeValue::INTEGER(val1), RuntimeValue::FLOAT(val2)) => (*val1 as f64).partial_cmp(val2),
+      (RuntimeValue::FLOAT(val1), RuntimeValue::INTEGER(val2)) => val1.partial_cmp(&(*val2 as f64)),
+      (RuntimeValue::STRING(val1), RuntimeValue::STRING(val2)) => Some(val1.cmp(val2)),
+      _ => None,
+    }
+  }
+
+  /// Checks two values for equality. Arrays are equal when their elements are pairwise equal,
+  /// functions when they are the same function, and values of different types are never equal.
+  /// Arrays containing themselves are equal when no difference is found by walking them.
+  ///
+  /// # Arguments
+  ///
+  /// * `other` - The value to compare with.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the values are equal, `false` otherwise.
+  pub fn equals(&self, other: &RuntimeValue) -> bool {
+    self.equals_within(other, &mut Vec::new())
+  }
+
+  /// Checks two values for equality, see `equals`.
+  ///
+  /// # Arguments
+  ///
+  /// * `other` - The value to compare with.
+  /// * `comparing` - The pairs of arrays being compared, the values are nested in them.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the values are equal, `false` otherwise.
+  fn equals_within(&self, other: &RuntimeValue, comparing: &mut Vec<(ArrayPtr, ArrayPtr)>) -> bool {
+    match (self, other) {
+      (RuntimeValue::INTEGER(val1), RuntimeValue::INTEGER(val2)) => val1 == val2,
+      (RuntimeValue::FLOAT(val1), RuntimeValue::FLOAT(val2)) => val1 == val2,
+      (RuntimeValue::STRING(val1), RuntimeValue::STRING(val2)) => val1 == val2,
+      (RuntimeValue::BOOL(val1), RuntimeValue::BOOL(val2)) => val1 == val2,
+      (RuntimeValue::NULL, RuntimeValue::NULL) => true,
+      // A pair already being compared is equal unless a difference is found elsewhere
+      (RuntimeValue::ARRAY(val1), RuntimeValue::ARRAY(val2))
+        if comparing.contains(&(Rc::as_ptr(val1), Rc::as_ptr(val2))) =>
+      {
+        true
+      }
+      (RuntimeValue::ARRAY(val1), RuntimeValue::ARRAY(val2)) => {
+        comparing.push((Rc::as_ptr(val1), Rc::as_ptr(val2)));
+        let equal: bool = Self::elements_equal(&val1.borrow(), &val2.borrow(), comparing);
+        comparing.pop();
+        equal
+      }
+      (RuntimeValue::FUNCTION(val1), RuntimeValue::FUNCTION(val2)) => Rc::ptr_eq(val1, val2),
+      (RuntimeValue::MODULE(val1), RuntimeValue::MODULE(val2)) => Rc::ptr_eq(val1, val2),
+      _ => false,
+    }
+  }
+
+  /// Checks two arrays' elements for pairwise equality.
+  ///
+  /// # Arguments
+  ///
+  /// * `val1` - The elements of the first array.
+  /// * `val2` - The elements of the second array.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the arrays have the same length and pairwise equal elements, `false` otherwise.
+  pub fn arrays_equal(val1: &[RuntimeValue], val2: &[RuntimeValue]) -> bool {
+    Self::elements_equal(val1, val2, &mut Vec::new())
+  }
+
+  /// Checks two arrays' elements for pairwise equality, see `equals`.
+  ///
+  /// # Arguments
+  ///
+  /// * `val1` - The elements of the first array.
+  /// * `val2` - The elements of the second array.
+  /// * `comparing` - The pairs of arrays being compared, the elements are nested in them.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the arrays have the same length and pairwise equal elements, `false` otherwise.
+  fn elements_equal(
+    val1: &[RuntimeValue],
+    val2: &[RuntimeValue],
+    comparing: &mut Vec<(ArrayPtr, ArrayPtr)>,
+  ) -> bool {
+    val1.len() == val2.len()
+      && val1
+        .iter()
+        .zip(val2)
+        .all(|(a, b)| a.equals_within(b, comparing))
+  }
+
+  /// Copies a value so that the copy shares no array with the original. Arrays are copied
+  /// recursively, other values are returned as-is since they cannot be mutated in place. An array
+  /// appearing several times in the value is copied once, so a copy of an array containing itself
+  /// contains the copy.
+  ///
+  /// # Returns
+  ///
+  /// * `RuntimeValue` - The independent copy.
+  pub fn deep_copy(&self) -> RuntimeValue {
+    self.deep_copy_within(&mut HashMap::new())
+  }
+
+  /// Copies a value so that the copy shares no array with the original, see `deep_copy`.
+  ///
+  /// # Arguments
+  ///
+  /// * `copies` - The copies of the arrays copied so far, by original array.
+  ///
+  /// # Returns
+  ///
+  /// * `RuntimeValue` - The independent copy.
+  fn deep_copy_within(
+    &self,
+    copies: &mut HashMap<ArrayPtr, Rc<RefCell<Vec<RuntimeValue>>>>,
+  ) -> RuntimeValue {
+    match self {
+      RuntimeValue::ARRAY(elements) => {
+        if let Some(copy) = copies.get(&Rc::as_ptr(elements)) {
+          return RuntimeValue::ARRAY(copy.clone());
+        }
+        // Registered before copying the elements, so that they can refer to the copy
+        let copy: Rc<RefCell<Vec<RuntimeValue>>> = Rc::new(RefCell::new(Vec::new()));
+        copies.insert(Rc::as_ptr(elements), copy.clone());
+        let copied: Vec<RuntimeValue> = elements
+          .borrow()
+          .iter()
+          .map(|element| element.deep_copy_within(copies))
+          .collect();
+        *copy.borrow_mut() = copied;
+        RuntimeValue::ARRAY(copy)
+      }
+      other => other.clone(),
+    }
+  }
+
+  /// Formats the value the way a user writes it, with integers written in the given base.
+  /// Digits above 9 are lowercase letters and negative integers are written with a leading `-`,
+  /// e.g. `-ff` in base 16. Integers nested in arrays use the same base, floats are always
+  /// written in decimal.
+  ///
+  /// # Arguments
+  ///
+  /// * `radix` - The base integers are written in, between 2 and 36.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The formatted value.
+  pub fn format_radix(&self, radix: u32) -> String {
+    let mut output: String = String::new();
+    // Writing to a String never fails
+    self
+      .write_radix(&mut output, radix, &mut Vec::new())
+      .unwrap();
+    output
+  }
+
+  /// Writes the value with integers in the given base, shared by `format_radix` and `Display`. An
+  /// array containing itself is written as `[...]` where it repeats.
+  ///
+  /// # Arguments
+  ///
+  /// * `out` - The destination of the formatted value.
+  /// * `radix` - The base integers are written in, between 2 and 36.
+  /// * `enclosing` - The arrays the value is nested in.
+  ///
+  /// # Returns
+  ///
+  /// * `fmt::Result` - The result of writing to `out`.
+  fn write_radix(
+    &self,
+    out: &mut impl fmt::Write,
+    radix: u32,
+    enclosing: &mut Vec<ArrayPtr>,
+  ) -> fmt::Result {
+    match self {
+      RuntimeValue::INTEGER(val) => {
+        // Widened so that the magnitude of the smallest integer fits, whatever the integer width
+        let mut magnitude: i128 = i128::from(*val).abs();
+        let mut digits: Vec<char> = Vec::new();
+        loop {
+          digits.push(char::from_digit((magnitude % i128::from(radix)) as u32, radix).unwrap());
+          magnitude /= i128::from(radix);
+          if magnitude == 0 {
+            break;
+          }
+        }
+        if *val < 0 {
+          out.write_char('-')?;
+        }
+        digits
+          .iter()
+          .rev()
+          .try_for_each(|digit| out.write_char(*digit))
+      }
+      // Display formatting never uses an exponent, e.g. `10000000000000000` for 1e16, but drops
+      // the decimal point of whole floats, which is added back so that `3.0` isn't shown as `3`
+      RuntimeValue::FLOAT(val) if val.is_nan() => write!(out, "nan"),
+      RuntimeValue::FLOAT(val) if val.is_infinite() => {
+        write!(out, "{}", if *val > 0.0 { "inf" } else { "-inf" })
+      }
+      RuntimeValue::FLOAT(val) if val.fract() == 0.0 => write!(out, "{}.0", val),
+      RuntimeValue::FLOAT(val) => write!(out, "{}", val),
+      RuntimeValue::STRING(val) => write!(out, "{}", val),
+      RuntimeValue::NULL => write!(out, "null"),
+      RuntimeValue::BOOL(val) => write!(out, "{}", val),
+      RuntimeValue::ARRAY(elements) if enclosing.contains(&Rc::as_ptr(elements)) => {
+        write!(out, "[...]")
+      }
+      RuntimeValue::ARRAY(elements) => {
+        enclosing.push(Rc::as_ptr(elements));
+        write!(out, "[")?;
+        for (i, element) in elements.borrow().iter().enumerate() {
+          if i > 0 {
+            write!(out, ", ")?;
+          }
+          match element {
+            RuntimeValue::STRING(val) => write!(out, "{:?}", val)?,
+            other => other.write_radix(out, radix, enclosing)?,
+          }
+        }
+        enclosing.pop();
+        write!(out, "]")
+      }
+      RuntimeValue::FUNCTION(func) => write!(out, "fn({})", func.get_params().join(", ")),
+      RuntimeValue::MODULE(module) => write!(out, "module {}", module.get_name()),
+    }
+  }
+
+  /// Formats the value across several lines, each element of a non-empty array on its own line
+  /// indented by two spaces per nesting level. An array containing itself, directly or through
+  /// nested arrays, is written as `[...]` where it repeats.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The formatted value, without a trailing newline.
+  pub fn format_pretty(&self) -> String {
+    let mut output: String = String::new();
+    self.write_pretty(&mut output, 0, &mut Vec::new());
+    output
+  }
+
+  /// Writes the value across several lines, see `format_pretty`.
+  ///
+  /// # Arguments
+  ///
+  /// * `out` - The destination of the formatted value.
+  /// * `depth` - The nesting level of the value, its nested elements are indented one more level.
+  /// * `enclosing` - The arrays the value is nested in, to detect an array containing itself.
+  fn write_pretty(&self, out: &mut String, depth: usize, enclosing: &mut Vec<ArrayPtr>) {
+    match self {
+      RuntimeValue::ARRAY(elements) if enclosing.contains(&Rc::as_ptr(elements)) => {
+        out.push_str("[...]")
+      }
+      RuntimeValue::ARRAY(elements) if elements.borrow().is_empty() => out.push_str("[]"),
+      RuntimeValue::ARRAY(elements) => {
+        enclosing.push(Rc::as_ptr(elements));
+        out.push_str("[\n");
+        let count: usize = elements.borrow().len();
+        for (i, element) in elements.borrow().iter().enumerate() {
+          out.push_str(&"  ".repeat(depth + 1));
+          match element {
+            RuntimeValue::STRING(val) => out.push_str(&format!("{:?}", val)),
+            other => other.write_pretty(out, depth + 1, enclosing),
+          }
+          out.push_str(if i + 1 < count { ",\n" } else { "\n" });
+        }
+        out.push_str(&"  ".repeat(depth));
+        out.push(']');
+        enclosing.pop();
+      }
+      other => out.push_str(&other.to_string()),
+    }
+  }
+}
+
+/// Formats values the way a user writes them, e.g. `[1, "a", null]`. Strings are written as-is,
+/// except inside arrays where they are quoted to keep elements apart. Integers are written in
+/// decimal, see `RuntimeValue::format_radix` for other bases.
+impl fmt::Display for RuntimeValue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.write_radix(f, 10, &mut Vec::new())
+  }
+}
+
+// Written like a derived implementation, except for arrays containing themselves, written as
+// `ARRAY([...])` where they repeat
+impl fmt::Debug for RuntimeValue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      RuntimeValue::INTEGER(val) => f.debug_tuple("INTEGER").field(val).finish(),
+      RuntimeValue::FLOAT(val) => f.debug_tuple("FLOAT").field(val).finish(),
+      RuntimeValue::STRING(val) => f.debug_tuple("STRING").field(val).finish(),
+      RuntimeValue::NULL => f.write_str("NULL"),
+      RuntimeValue::BOOL(val) => f.debug_tuple("BOOL").field(val).finish(),
+      RuntimeValue::ARRAY(elements) => {
+        let ptr: ArrayPtr = Rc::as_ptr(elements);
+        if DEBUG_ENCLOSING.with(|enclosing| enclosing.borrow().contains(&ptr)) {
+          return f.write_str("ARRAY([...])");
+        }
+        DEBUG_ENCLOSING.with(|enclosing| enclosing.borrow_mut().push(ptr));
+        let result = f.debug_tuple("ARRAY").field(elements).finish();
+        DEBUG_ENCLOSING.with(|enclosing| enclosing.borrow_mut().pop());
+        result
+      }
+      RuntimeValue::FUNCTION(func) => f.debug_tuple("FUNCTION").field(func).finish(),
+      RuntimeValue::MODULE(module) => f.debug_tuple("MODULE").field(module).finish(),
+    }
+  }
 }
 
 /// Represents a node in the Abstract Syntax Tree (AST).
@@ -36,152 +471,1518 @@ impl ASTree {
   ///
   /// # Arguments
   ///
-  /// * `token` - The token associated with this AST node.
+  /// * `token` - The token associated with this AST node.
+  ///
+  /// # Returns
+  ///
+  /// * A new ASTree instance.
+  pub fn new(token: Token) -> ASTree {
+    ASTree {
+      children: Vec::new(),
+      token,
+    }
+  }
+
+  /// Appends a child ASTree node to this node.
+  ///
+  /// # Arguments
+  ///
+  /// * `child` - The child ASTree node to append.
+  pub fn append(&mut self, child: ASTree) {
+    self.children.push(Rc::new(child));
+  }
+
+  /// Returns a reference to the token of this ASTree node.
+  ///
+  /// # Returns
+  ///
+  /// * A reference to this node's token.
+  pub fn get_token(&self) -> &Token {
+    &self.token
+  }
+
+  /// Returns the children of this ASTree node, in order.
+  ///
+  /// # Returns
+  ///
+  /// * A slice of this node's children.
+  pub fn get_children(&self) -> &[Rc<ASTree>] {
+    &self.children
+  }
+
+  /// Returns the condition of a `while` or `for` loop node.
+  pub fn get_loop_condition(&self) -> &ASTree {
+    // while: condition, body... / for: initialization, condition, step, body...
+    match self.token.get_type() {
+      TokenType::FOR => &self.children[1],
+      _ => &self.children[0],
+    }
+  }
+
+  /// Returns the body of a loop node.
+  pub fn get_loop_body(&self) -> &ASTree {
+    // loop: body... / repeat: count, body...
+    match self.token.get_type() {
+      TokenType::FOR => &self.children[3],
+      TokenType::LOOP => &self.children[0],
+      _ => &self.children[1],
+    }
+  }
+
+  /// Returns the `else` block of a `while` or `for` loop node, if any, which directly follows the
+  /// body.
+  pub fn get_loop_else(&self) -> Option<&ASTree> {
+    let else_index: usize = match self.token.get_type() {
+      TokenType::FOR => 4,
+      _ => 2,
+    };
+    self
+      .children
+      .get(else_index)
+      .filter(|child| matches!(child.get_type(), TokenType::BLOCK(_)))
+      .map(|child| &**child)
+  }
+
+  /// Returns the label of a loop node, if any, which is its last child.
+  pub fn get_loop_label(&self) -> Option<&String> {
+    self
+      .children
+      .last()
+      .filter(|child| matches!(child.get_type(), TokenType::IDENTIFIER))
+      .map(|label| label.token.get_value())
+  }
+
+  /// Returns a reference to the token type of this ASTree node.
+  ///
+  /// # Returns
+  ///
+  /// * A reference to the TokenType of this node's token.
+  pub fn get_type(&self) -> &TokenType {
+    self.token.get_type()
+  }
+
+  /// Serializes this ASTree to JSON, each node being an object with its token's type, value,
+  /// position, line and column, and the array of its children. Keys are always written in the same order.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The JSON representation of the tree, on a single line.
+  pub fn to_json(&self) -> String {
+    let children: Vec<String> = self.children.iter().map(|child| child.to_json()).collect();
+    format!(
+      "{{\"type\":{},\"value\":{},\"position\":{},\"line\":{},\"column\":{},\"children\":[{}]}}",
+      Self::json_string(&format!("{:?}", self.token.get_type())),
+      Self::json_string(self.token.get_value()),
+      self.token.get_position(),
+      self.token.get_line(),
+      self.token.get_column(),
+      children.join(",")
+    )
+  }
+
+  /// Serializes this ASTree to a Graphviz DOT graph, e.g. to render it with `dot -Tpng`. Each
+  /// node is labeled with its token's type and value, and has an edge to each of its children.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The DOT representation of the tree.
+  pub fn to_dot(&self) -> String {
+    let mut output: String = String::from("digraph ast {\n");
+    self.write_dot(&mut output, &mut 0);
+    output.push_str("}\n");
+    output
+  }
+
+  /// Writes this node and its subtree as DOT statements, numbering nodes in pre-order so that
+  /// every node gets a unique id.
+  ///
+  /// # Arguments
+  ///
+  /// * `output` - The DOT graph being written.
+  /// * `next_id` - The id of the next written node, incremented for each node.
+  ///
+  /// # Returns
+  ///
+  /// * `usize` - The id of this node.
+  fn write_dot(&self, output: &mut String, next_id: &mut usize) -> usize {
+    let id: usize = *next_id;
+    *next_id += 1;
+    output.push_str(&format!(
+      "  n{} [label={}];\n",
+      id,
+      Self::dot_string(&format!(
+        "{:?}\n{}",
+        self.token.get_type(),
+        self.token.get_value()
+      ))
+    ));
+    for child in &self.children {
+      let child_id: usize = child.write_dot(output, next_id);
+      output.push_str(&format!("  n{} -> n{};\n", id, child_id));
+    }
+    id
+  }
+
+  /// Formats a string as a DOT string literal, escaping quotes and backslashes. Line breaks are
+  /// written as DOT's `\n` escape, so multi-line labels stay on one line of the graph.
+  ///
+  /// # Arguments
+  ///
+  /// * `val` - The string to format.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The quoted and escaped string.
+  fn dot_string(val: &str) -> String {
+    let mut output: String = String::from("\"");
+    for character in val.chars() {
+      match character {
+        '"' => output.push_str("\\\""),
+        '\\' => output.push_str("\\\\"),
+        '\n' => output.push_str("\\n"),
+        '\r' => {}
+        other => output.push(other),
+      }
+    }
+    output.push('"');
+    output
+  }
+
+  /// Formats a string as a JSON string literal, escaping quotes, backslashes and control
+  /// characters.
+  ///
+  /// # Arguments
+  ///
+  /// * `val` - The string to format.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The quoted and escaped string.
+  fn json_string(val: &str) -> String {
+    let mut output: String = String::from("\"");
+    for character in val.chars() {
+      match character {
+        '"' => output.push_str("\\\""),
+        '\\' => output.push_str("\\\\"),
+        '\n' => output.push_str("\\n"),
+        '\r' => output.push_str("\\r"),
+        '\t' => output.push_str("\\t"),
+        other if other.is_control() => output.push_str(&format!("\\u{:04x}", other as u32)),
+        other => output.push(other),
+      }
+    }
+    output.push('"');
+    output
+  }
+
+  /// Walks this ASTree with a visitor, calling the visitor method matching this node before
+  /// visiting each of its children in order.
+  ///
+  /// # Arguments
+  ///
+  /// * `visitor` - The visitor to walk the tree with.
+  pub fn accept(&self, visitor: &mut dyn Visitor) {
+    match self.token.get_type() {
+      TokenType::NUMERIC => visitor.visit_numeric(self),
+      TokenType::STRING => visitor.visit_string(self),
+      TokenType::BOOL => visitor.visit_bool(self),
+      TokenType::IDENTIFIER => visitor.visit_identifier(self),
+      TokenType::CALL | TokenType::METHODCALL => visitor.visit_fn_call(self),
+      TokenType::APPLY => visitor.visit_apply(self),
+      TokenType::BINARYOP => visitor.visit_binary_op(self),
+      TokenType::NOT => visitor.visit_not(self),
+      TokenType::UNARYOP => visitor.visit_unary_op(self),
+      TokenType::ASSIGN => visitor.visit_assign(self),
+      TokenType::WALRUS => visitor.visit_walrus(self),
+      TokenType::GLOBAL => visitor.visit_global(self),
+      TokenType::CONST => visitor.visit_const(self),
+      TokenType::THROW => visitor.visit_throw(self),
+      TokenType::RETURN => visitor.visit_return(self),
+      TokenType::INCLUDE => visitor.visit_include(self),
+      TokenType::IMPORT => visitor.visit_import(self),
+      TokenType::BREAK => visitor.visit_break(self),
+      TokenType::CONTINUE => visitor.visit_continue(self),
+      TokenType::IF => visitor.visit_if(self),
+      TokenType::WHILE => visitor.visit_while(self),
+      TokenType::FOR => visitor.visit_for(self),
+      TokenType::LOOP => visitor.visit_loop(self),
+      TokenType::REPEAT => visitor.visit_repeat(self),
+      TokenType::FN => visitor.visit_fn_def(self),
+      TokenType::LAMBDA => visitor.visit_lambda(self),
+      TokenType::BLOCK(_) => visitor.visit_block(self),
+      TokenType::ARRAY => visitor.visit_array(self),
+      TokenType::INDEX => visitor.visit_index(self),
+      _ => {}
+    }
+    for child in &self.children {
+      child.accept(visitor);
+    }
+  }
+
+  /// Evaluates a binary operation between two integer values.
+  ///
+  /// # Arguments
+  ///
+  /// * `val1` - The first integer value.
+  /// * `val2` - The second integer value.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_binop_ints(&self, val1: Integer, val2: Integer) -> Result<RuntimeValue, String> {
+    match self.token.get_value().as_str() {
+      "+" => Ok(RuntimeValue::INTEGER(val1.wrapping_add(val2))),
+      "-" => Ok(RuntimeValue::INTEGER(val1.wrapping_sub(val2))),
+      "*" => Ok(RuntimeValue::INTEGER(val1.wrapping_mul(val2))),
+      "/" => {
+        if val2 == 0 {
+          Err(format!(
+            "Division by zero error at {}",
+            self.token.get_location()
+          ))
+        } else {
+          Ok(RuntimeValue::INTEGER(val1.wrapping_div(val2)))
+        }
+      }
+      "%" => {
+        if val2 == 0 {
+          Err(format!(
+            "Modulo by zero error at {}",
+            self.token.get_location()
+          ))
+        } else {
+          Ok(RuntimeValue::INTEGER(val1.wrapping_rem(val2)))
+        }
+      }
+      "==" => Ok(RuntimeValue::BOOL(val1 == val2)),
+      "!=" => Ok(RuntimeValue::BOOL(val1 != val2)),
+      _ => Err(format!(
+        "Unsupported binary operator: '{}' between integers, at {}",
+        self.token.get_value(),
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Evaluates a binary operation between two float values. Ordering comparisons only reach it
+  /// when a value is NaN, they are then false like in IEEE 754.
+  ///
+  /// # Arguments
+  ///
+  /// * `val1` - The first float value.
+  /// * `val2` - The second float value.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_binop_floats(&self, val1: f64, val2: f64) -> Result<RuntimeValue, String> {
+    match self.token.get_value().as_str() {
+      "+" => Ok(RuntimeValue::FLOAT(val1 + val2)),
+      "-" => Ok(RuntimeValue::FLOAT(val1 - val2)),
+      "*" => Ok(RuntimeValue::FLOAT(val1 * val2)),
+      "/" => {
+        if val2 == 0.0 {
+          Err(format!(
+            "Division by zero error at {}",
+            self.token.get_location()
+          ))
+        } else {
+          Ok(RuntimeValue::FLOAT(val1 / val2))
+        }
+      }
+      "==" => Ok(RuntimeValue::BOOL(val1 == val2)),
+      "!=" => Ok(RuntimeValue::BOOL(val1 != val2)),
+      "<" | "<=" | ">" | ">=" => Ok(RuntimeValue::BOOL(false)),
+      _ => Err(format!(
+        "Unsupported binary operator: '{}' between floats, at {}",
+        self.token.get_value(),
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Evaluates a binary operation between two boolean values.
+  ///
+  /// # Arguments
+  ///
+  /// * `val1` - The first boolean value.
+  /// * `val2` - The second boolean value.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_binop_bools(&self, val1: bool, val2: bool) -> Result<RuntimeValue, String> {
+    match self.token.get_value().as_str() {
+      "&&" => Ok(RuntimeValue::BOOL(val1 && val2)),
+      "||" => Ok(RuntimeValue::BOOL(val1 || val2)),
+      "==" => Ok(RuntimeValue::BOOL(val1 == val2)),
+      "!=" => Ok(RuntimeValue::BOOL(val1 != val2)),
+      _ => Err(format!(
+        "Unsupported binary operator: '{}' between booleans, at {}",
+        self.token.get_value(),
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Evaluates a binary operation between two string values.
+  ///
+  /// # Arguments
+  ///
+  /// * `val1` - The first string value.
+  /// * `val2` - The second string value.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_binop_strings(&self, val1: &String, val2: &String) -> Result<RuntimeValue, String> {
+    match self.token.get_value().as_str() {
+      "+" => Ok(RuntimeValue::STRING(format!("{}{}", val1, val2))),
+      "==" => Ok(RuntimeValue::BOOL(val1 == val2)),
+      "!=" => Ok(RuntimeValue::BOOL(val1 != val2)),
+      _ => Err(format!(
+        "Unsupported binary operator: '{}' between strings, at {}",
+        self.token.get_value(),
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Evaluates a binary operation between two array values.
+  ///
+  /// # Arguments
+  ///
+  /// * `val1` - The first array value.
+  /// * `val2` - The second array value.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_binop_arrays(
+    &self,
+    val1: &[RuntimeValue],
+    val2: &[RuntimeValue],
+  ) -> Result<RuntimeValue, String> {
+    match self.token.get_value().as_str() {
+      "+" => Ok(RuntimeValue::ARRAY(Rc::new(RefCell::new(
+        [val1, val2].concat(),
+      )))),
+      "==" => Ok(RuntimeValue::BOOL(RuntimeValue::arrays_equal(val1, val2))),
+      "!=" => Ok(RuntimeValue::BOOL(!RuntimeValue::arrays_equal(val1, val2))),
+      _ => Err(format!(
+        "Unsupported binary operator: '{}' between arrays, at {}",
+        self.token.get_value(),
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Evaluates a membership test, `x in arr` checks whether an element of `arr` equals `x`,
+  /// `sub in str` whether `sub` is a substring of `str`.
+  ///
+  /// # Arguments
+  ///
+  /// * `value` - The value to look for.
+  /// * `container` - The array or string to look in.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::BOOL)` if evaluation is successful.
+  /// * `Err(String)` if the container can't contain the value.
+  fn eval_membership(
+    &self,
+    value: &RuntimeValue,
+    container: &RuntimeValue,
+  ) -> Result<RuntimeValue, String> {
+    match (value, container) {
+      (_, RuntimeValue::ARRAY(elements)) => Ok(RuntimeValue::BOOL(
+        elements
+          .borrow()
+          .iter()
+          .any(|element| element.equals(value)),
+      )),
+      (RuntimeValue::STRING(sub), RuntimeValue::STRING(val)) => {
+        Ok(RuntimeValue::BOOL(val.contains(sub.as_str())))
+      }
+      _ => Err(format!(
+        "Operator 'in' expects an array or two strings, at {}\n Left operand type: {:?}\n Right operand type: {:?}",
+        self.token.get_location(),
+        value,
+        container
+      )),
+    }
+  }
+
+  /// Evaluates a binary operation ASTree node.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_binary_op(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two children from parser: left and right operands
+    let param1: RuntimeValue = self.children[0].eval(ctx)?;
+    let param2: RuntimeValue = self.children[1].eval(ctx)?;
+
+    if self.token.get_value() == "in" {
+      return self.eval_membership(&param1, &param2);
+    }
+
+    // Ordering comparisons apply to any pair of values that can be ordered, other pairs fall
+    // through to the per-type operations to report the error
+    if let Option::Some(order) = param1.compare(&param2) {
+      match self.token.get_value().as_str() {
+        "<" => return Ok(RuntimeValue::BOOL(order.is_lt())),
+        "<=" => return Ok(RuntimeValue::BOOL(order.is_le())),
+        ">" => return Ok(RuntimeValue::BOOL(order.is_gt())),
+        ">=" => return Ok(RuntimeValue::BOOL(order.is_ge())),
+        _ => {}
+      }
+    }
+
+    match (&param1, &param2) {
+      (RuntimeValue::INTEGER(val1), RuntimeValue::INTEGER(val2)) => {
+        self.eval_binop_ints(*val1, *val2)
+      }
+
+      (RuntimeValue::FLOAT(val1), RuntimeValue::FLOAT(val2)) => {
+        self.eval_binop_floats(*val1, *val2)
+      }
+
+      (RuntimeValue::BOOL(val1), RuntimeValue::BOOL(val2)) => self.eval_binop_bools(*val1, *val2),
+
+      (RuntimeValue::STRING(val1), RuntimeValue::STRING(val2)) => {
+        self.eval_binop_strings(val1, val2)
+      }
+
+      (RuntimeValue::ARRAY(val1), RuntimeValue::ARRAY(val2)) => {
+        self.eval_binop_arrays(&val1.borrow(), &val2.borrow())
+      }
+
+      _ => Err(format!(
+        "Type mismatch for binary operation {} at {}\n Left operand type: {:?}\n Right operand type: {:?}",
+        self.token.get_value(),
+        self.token.get_location(),
+        param1,
+        param2
+      )),
+    }
+  }
+
+  /// Evaluates a logical negation ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::BOOL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_not(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting one child from parser: the operand
+    match self.children[0].eval(ctx)? {
+      RuntimeValue::BOOL(val) => Ok(RuntimeValue::BOOL(!val)),
+      other => Err(format!(
+        "Unsupported operand for '{}': {:?}, at {}",
+        self.token.get_value(),
+        other,
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Evaluates a numeric negation ASTree node. Negating the smallest integer wraps around to
+  /// itself, like other integer arithmetic.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_negate(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting one child from parser: the operand
+    match self.children[0].eval(ctx)? {
+      RuntimeValue::INTEGER(val) => Ok(RuntimeValue::INTEGER(val.wrapping_neg())),
+      RuntimeValue::FLOAT(val) => Ok(RuntimeValue::FLOAT(-val)),
+      other => Err(format!(
+        "Unsupported operand for '{}': {:?}, at {}",
+        self.token.get_value(),
+        other,
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Evaluates an if-statement ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_if(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two or three children from parser: condition, then-branch, else-branch (optional)
+    let condition_result: bool = match self.children[0].eval(ctx)? {
+      RuntimeValue::BOOL(val) => val,
+      other => {
+        return Err(format!(
+          "If condition didn't evaluate to Boolean value, is: {:?}, at {}",
+          other,
+          self.token.get_location()
+        ));
+      }
+    };
+
+    if condition_result {
+      // Then-branch
+      self.children[1].eval(ctx)
+    } else if self.children.len() == 3 {
+      // Else-branch
+      self.children[2].eval(ctx)
+    } else {
+      Ok(RuntimeValue::NULL)
+    }
+  }
+
+  /// Evaluates a while-loop ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_while(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two to four children from parser: condition, body, optional else block and
+    // optional label
+    let label: Option<&String> = self.get_loop_label();
+    let mut ran: bool = false;
+    while match self.get_loop_condition().eval(ctx)? {
+      RuntimeValue::BOOL(val) => val,
+      other => {
+        return Err(format!(
+          "While condition didn't evaluate to Boolean value, is: {:?}, at {}",
+          other,
+          self.token.get_location()
+        ));
+      }
+    } {
+      ran = true;
+      self.get_loop_body().eval(ctx)?;
+      if Self::should_exit_loop(ctx, label) {
+        break;
+      }
+    }
+    self.eval_loop_else(ran, ctx)
+  }
+
+  /// Evaluates a `loop` ASTree node, running its body until a `break` exits it.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_loop(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting one or two children from parser: body and optional label
+    let label: Option<&String> = self.get_loop_label();
+    loop {
+      self.get_loop_body().eval(ctx)?;
+      if Self::should_exit_loop(ctx, label) {
+        return Ok(RuntimeValue::NULL);
+      }
+    }
+  }
+
+  /// Evaluates a repeat ASTree node, running its body as many times as its count, which is
+  /// evaluated once before the first iteration. A count of zero or less runs the body zero times.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_repeat(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two or three children from parser: count, body and optional label
+    let label: Option<&String> = self.get_loop_label();
+    let count: Integer = match self.children[0].eval(ctx)? {
+      RuntimeValue::INTEGER(val) => val,
+      other => {
+        return Err(format!(
+          "Repeat count didn't evaluate to an integer, is: {:?}, at {}",
+          other,
+          self.token.get_location()
+        ));
+      }
+    };
+    for _ in 0..count.max(0) {
+      self.get_loop_body().eval(ctx)?;
+      if Self::should_exit_loop(ctx, label) {
+        break;
+      }
+    }
+    Ok(RuntimeValue::NULL)
+  }
+
+  /// Evaluates the statements of a block ASTree node in order, in the current scope.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` holding the value of the last evaluated statement, NULL for an empty
+  ///   block.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_statements(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    let mut last_value: RuntimeValue = RuntimeValue::NULL;
+    for child in &self.children {
+      last_value = child.eval(ctx)?;
+      // The rest of the block is skipped until a loop consumes the change of control flow
+      if ctx.has_control_flow() {
+        break;
+      }
+    }
+    Ok(last_value)
+  }
+
+  /// Evaluates the statements of a parsed line in the context's current scope, as a REPL does, so
+  /// that the variables and functions they define remain for the next lines.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context kept between the lines, into which the front-end pushed the global
+  ///   scope once.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` holding the value of the last statement not evaluating to NULL, to
+  ///   display after `x = 1; x + 1` for instance, or NULL if every statement did. A `return`
+  ///   ends the line with its value, the following lines still run.
+  /// * `Err(String)` if an error occurs during evaluation, the statements before it keep their
+  ///   effects.
+  pub fn eval_line(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    let mut last_value: RuntimeValue = RuntimeValue::NULL;
+    for child in &self.children {
+      let value: RuntimeValue = child.eval(ctx)?;
+      if let Option::Some(returned) = ctx.take_return() {
+        return Ok(returned);
+      }
+      if !matches!(value, RuntimeValue::NULL) {
+        last_value = value;
+      }
+    }
+    Ok(last_value)
+  }
+
+  /// Evaluates a for-loop ASTree node. The loop gets its own scope, wrapping every iteration, so
+  /// that variables set by its initialization or step aren't visible after the loop.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_for(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    ctx.push_named_scope("for_scope".to_string());
+    let result = self.eval_for_iterations(ctx);
+    ctx.pop_scope();
+    result
+  }
+
+  /// Evaluates the initialization and iterations of a for-loop ASTree node, within its scope.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_for_iterations(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting four to six children from parser: initialization, condition, step, body,
+    // optional else block and optional label
+    let label: Option<&String> = self.get_loop_label();
+    let mut ran: bool = false;
+    self.children[0].eval(ctx)?;
+    while match self.get_loop_condition().eval(ctx)? {
+      RuntimeValue::BOOL(val) => val,
+      other => {
+        return Err(format!(
+          "For condition didn't evaluate to Boolean value, is: {:?}, at {}",
+          other,
+          self.token.get_location()
+        ));
+      }
+    } {
+      ran = true;
+      self.get_loop_body().eval(ctx)?;
+      if Self::should_exit_loop(ctx, label) {
+        break;
+      }
+      self.children[2].eval(ctx)?;
+    }
+    self.eval_loop_else(ran, ctx)
+  }
+
+  /// Evaluates the `else` block of a loop ASTree node, if it has one and its body never ran.
+  ///
+  /// # Arguments
+  ///
+  /// * `ran` - Whether the body of the loop ran at least once.
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_loop_else(&self, ran: bool, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    if !ran && let Some(else_block) = self.get_loop_else() {
+      else_block.eval(ctx)?;
+    }
+    Ok(RuntimeValue::NULL)
+  }
+
+  /// Consumes the change of control flow raised by the body of a loop, if it targets the loop.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  /// * `label` - The label of the loop, if any.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the loop must stop, because of a `break` or of a change of control flow
+  ///   targeting an outer loop, `false` if it must go on with its next iteration.
+  fn should_exit_loop(ctx: &mut Context, label: Option<&String>) -> bool {
+    if !ctx.has_control_flow() {
+      return false;
+    }
+    match ctx.take_control_flow(label) {
+      Option::Some(ControlFlow::BREAK(_)) => true,
+      Option::Some(ControlFlow::CONTINUE(_)) => false,
+      // Targets an outer loop or the enclosing function call, which is left pending for it
+      Option::None | Option::Some(ControlFlow::RETURN(_)) => true,
+    }
+  }
+
+  /// Evaluates a `break` or `continue` ASTree node, raising the matching change of control flow
+  /// for the enclosing blocks and loops.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` once the change of control flow is raised.
+  fn eval_loop_control(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting 0 or 1 child from parser: the label of the targeted loop
+    let label: Option<String> = self
+      .children
+      .first()
+      .map(|label| label.token.get_value().clone());
+    if matches!(self.token.get_type(), TokenType::BREAK) {
+      ctx.set_control_flow(ControlFlow::BREAK(label));
+    } else {
+      ctx.set_control_flow(ControlFlow::CONTINUE(label));
+    }
+    Ok(RuntimeValue::NULL)
+  }
+
+  /// Evaluates an assignment ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_assign(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two children from parser: identifier, index or array of identifiers target, and
+    // value
+    let value = self.children[1].eval(ctx)?;
+    if matches!(self.children[0].get_type(), TokenType::INDEX) {
+      self.children[0].eval_index_assign(value, ctx)?;
+    } else if matches!(self.children[0].get_type(), TokenType::ARRAY) {
+      self.eval_destructuring_assign(value, ctx)?;
+    } else {
+      let name = self.children[0].token.get_value();
+      self.check_not_constant(name, ctx.is_constant(name))?;
+      ctx.set_variable(name.clone(), value);
+    }
+    Ok(RuntimeValue::NULL)
+  }
+
+  /// Assigns the elements of an array to the identifiers of a destructuring assignment's target,
+  /// in order.
+  ///
+  /// # Arguments
+  ///
+  /// * `value` - The assigned value, which must be an array with one element per identifier.
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(())` if every identifier was assigned.
+  /// * `Err(String)` if the value isn't an array of the right length or an identifier is a
+  ///   constant, nothing is assigned then.
+  fn eval_destructuring_assign(
+    &self,
+    value: RuntimeValue,
+    ctx: &mut Context,
+  ) -> Result<(), String> {
+    let targets: &Vec<Rc<ASTree>> = &self.children[0].children;
+    let elements: Vec<RuntimeValue> = match value {
+      RuntimeValue::ARRAY(elements) => elements.borrow().clone(),
+      other => {
+        return Err(format!(
+          "Cannot destructure {:?}, expected an array, at {}",
+          other,
+          self.token.get_location()
+        ));
+      }
+    };
+    if elements.len() != targets.len() {
+      return Err(format!(
+        "Cannot destructure an array of {} element(s) into {} variable(s), at {}",
+        elements.len(),
+        targets.len(),
+        self.token.get_location()
+      ));
+    }
+    for target in targets {
+      let name = target.token.get_value();
+      self.check_not_constant(name, ctx.is_constant(name))?;
+    }
+    for (target, element) in targets.iter().zip(elements) {
+      ctx.set_variable(target.token.get_value().clone(), element);
+    }
+    Ok(())
+  }
+
+  /// Evaluates an assignment expression ASTree node, assigning like `=` does.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` holding the assigned value.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_walrus(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two children from parser: identifier and value
+    let value = self.children[1].eval(ctx)?;
+    let name = self.children[0].token.get_value();
+    self.check_not_constant(name, ctx.is_constant(name))?;
+    ctx.set_variable(name.clone(), value.clone());
+    Ok(value)
+  }
+
+  /// Checks that a variable about to be assigned is not a constant.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the assigned variable.
+  /// * `constant` - Whether the assigned variable is a constant.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(())` if the variable can be assigned.
+  /// * `Err(String)` if the variable is a constant.
+  fn check_not_constant(&self, name: &String, constant: bool) -> Result<(), String> {
+    if constant {
+      return Err(format!(
+        "Cannot reassign constant '{}', at {}",
+        name,
+        self.token.get_location()
+      ));
+    }
+    Ok(())
+  }
+
+  /// Evaluates a constant declaration ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_const(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two children from parser: identifier and value
+    let name = self.children[0].token.get_value();
+    let value = self.children[1].eval(ctx)?;
+    self.check_not_constant(name, ctx.is_constant(name))?;
+    ctx.set_constant(name.clone(), value);
+    Ok(RuntimeValue::NULL)
+  }
+
+  /// Evaluates a throw ASTree node, which always fails with the thrown value.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Err(String)` describing the thrown value, or the error that occurred while evaluating it.
+  fn eval_throw(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting one child from parser: the thrown expression
+    let value = self.children[0].eval(ctx)?;
+    Err(format!(
+      "Uncaught throw: {}, at {}",
+      value,
+      self.token.get_location()
+    ))
+  }
+
+  /// Evaluates a return ASTree node, raising a change of control flow that skips the rest of the
+  /// enclosing blocks and loops until the function call, or the program, ends.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` holding the returned value, NULL without an expression.
+  /// * `Err(String)` if an error occurs while evaluating the returned expression.
+  fn eval_return(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting zero or one child from parser: the returned expression
+    let value: RuntimeValue = match self.children.first() {
+      Option::Some(expression) => expression.eval(ctx)?,
+      Option::None => RuntimeValue::NULL,
+    };
+    ctx.set_control_flow(ControlFlow::RETURN(value.clone()));
+    Ok(value)
+  }
+
+  /// Evaluates an include ASTree node, lexing, parsing and evaluating the statements of another
+  /// file in the current scope. Include statements are only allowed at the top level, so this is
+  /// the global scope and the file's variables and functions become globals.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if the file is missing, already being included, or has an error.
+  fn eval_include(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting one child from parser: the path string
+    self.eval_source_file(ctx)
+  }
+
+  /// Evaluates an import ASTree node, evaluating the statements of another file in a frame of
+  /// their own and binding the functions they define to a module. The file sees the globals but
+  /// its own variables are not kept, except for those captured by its functions.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if the file is missing, already being imported, or has an error.
+  fn eval_import(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two children from parser: path string and module name
+    let name: &String = self.children[1].token.get_value();
+    self.check_not_constant(name, ctx.is_constant(name))?;
+    ctx.push_frame(format!("{} module", name));
+    let result = self.eval_source_file(ctx);
+    let functions = ctx.take_scope_functions();
+    ctx.pop_frame();
+    result?;
+    ctx.set_variable(
+      name.clone(),
+      RuntimeValue::MODULE(Rc::new(Module::new(name.clone(), functions))),
+    );
+    Ok(RuntimeValue::NULL)
+  }
+
+  /// Lexes, parses and evaluates the statements of the file named by the first child of an
+  /// include or import node, in the current scope. Paths are resolved relative to the file being
+  /// evaluated.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if the file is missing, already being evaluated, or has an error.
+  fn eval_source_file(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    let location: String = self.token.get_location();
+    let (verb, participle) = match self.token.get_type() {
+      TokenType::INCLUDE => ("include", "included"),
+      _ => ("import", "imported"),
+    };
+    let path: PathBuf = ctx.resolve_include(self.children[0].token.get_value());
+    let source_error = |error: String| {
+      format!(
+        "{}, in '{}' {} at {}",
+        error,
+        path.display(),
+        participle,
+        location
+      )
+    };
+    let canonical_path: PathBuf = fs::canonicalize(&path)
+      .map_err(|error| format!("Cannot {} '{}': {}", verb, path.display(), error))
+      .map_err(|error| format!("{}, at {}", error, location))?;
+    ctx
+      .enter_source_file(canonical_path.clone())
+      .map_err(|error| format!("{}, at {}", error, location))?;
+    let result = fs::read_to_string(&canonical_path)
+      .map_err(|error| format!("Cannot read file: {}", error))
+      .and_then(|source| {
+        let mut lexer: Lexer = Lexer::new();
+        lexer.set_input(source);
+        let tokens = lexer
+          .tokenize()
+          .map_err(|error| format!("Error during lexing: {}", error))?;
+        let mut parser: Parser = Parser::new();
+        parser.set_tokens(tokens);
+        parser
+          .parse()
+          .map_err(|errors| format!("Error during parsing: {}", errors.join("\n")))
+      })
+      // The statements are evaluated without the included file's own global block, which would
+      // bind them in a scope of their own
+      .and_then(|tree| tree.eval_statements(ctx));
+    ctx.exit_source_file();
+    if result.is_err() {
+      // The location recorded in the file would be read as one in the including file
+      ctx.set_error_location(self.token.get_line(), self.token.get_column());
+    }
+    result.map(|_| RuntimeValue::NULL).map_err(source_error)
+  }
+
+  /// Evaluates a numeric literal ASTree node.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::INTEGER)` or `Ok(RuntimeValue::FLOAT)` holding the literal's value.
+  /// * `Err(String)` if the literal isn't a valid number, which the lexer rules out for the
+  ///   trees it builds.
+  fn eval_numeric(&self) -> Result<RuntimeValue, String> {
+    // The lexer only lets a decimal point into numeric literals of floats
+    if self.token.get_value().contains('.') {
+      return self
+        .token
+        .get_value()
+        .parse::<f64>()
+        .map(RuntimeValue::FLOAT)
+        .map_err(|error| {
+          format!(
+            "Invalid float literal '{}': {}, at {}",
+            self.token.get_value(),
+            error,
+            self.token.get_location()
+          )
+        });
+    }
+    self
+      .token
+      .get_value()
+      .parse::<Integer>()
+      .map(RuntimeValue::INTEGER)
+      .map_err(|error| {
+        format!(
+          "Invalid integer literal '{}': {}, at {}",
+          self.token.get_value(),
+          error,
+          self.token.get_location()
+        )
+      })
+  }
+
+  /// Evaluates a global assignment ASTree node, which always assigns in the global scope.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_global(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two children from parser: identifier and value
+    let name = self.children[0].token.get_value();
+    let value = self.children[1].eval(ctx)?;
+    self.check_not_constant(name, ctx.is_global_constant(name))?;
+    ctx.set_global_variable(name.clone(), value);
+    Ok(RuntimeValue::NULL)
+  }
+
+  /// Evaluates an array literal ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::ARRAY)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_array(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting 0 or more children from parser: elements...
+    let mut elements: Vec<RuntimeValue> = Vec::new();
+    for child in &self.children {
+      elements.push(child.eval(ctx)?);
+    }
+    Ok(RuntimeValue::ARRAY(Rc::new(RefCell::new(elements))))
+  }
+
+  /// Evaluates the index child of an index ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(usize)` if the index evaluates to a non-negative integer.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_index_position(&self, ctx: &mut Context) -> Result<usize, String> {
+    match self.children[1].eval(ctx)? {
+      RuntimeValue::INTEGER(val) if val >= 0 => Ok(val as usize),
+      other => Err(format!(
+        "Index must be a non-negative integer, is: {:?}, at {}",
+        other,
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Formats the error for an out of bounds index.
+  ///
+  /// # Arguments
+  ///
+  /// * `index` - The out of bounds index.
+  /// * `len` - The length of the indexed value.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The error message.
+  fn index_out_of_bounds(&self, index: usize, len: usize) -> String {
+    format!(
+      "Index {} out of bounds for length {}, at {}",
+      index,
+      len,
+      self.token.get_location()
+    )
+  }
+
+  /// Evaluates an index ASTree node, reading an element of an array or a character of a string.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_index(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting two children from parser: indexed value and index
+    let target: RuntimeValue = self.children[0].eval(ctx)?;
+    let index: usize = self.eval_index_position(ctx)?;
+    match target {
+      RuntimeValue::ARRAY(elements) => {
+        let elements = elements.borrow();
+        match elements.get(index) {
+          Option::Some(element) => Ok(element.clone()),
+          Option::None => Err(self.index_out_of_bounds(index, elements.len())),
+        }
+      }
+      RuntimeValue::STRING(val) => match val.chars().nth(index) {
+        Option::Some(character) => Ok(RuntimeValue::STRING(character.to_string())),
+        Option::None => Err(self.index_out_of_bounds(index, val.chars().count())),
+      },
+      other => Err(format!(
+        "Cannot index into {:?}, at {}",
+        other,
+        self.token.get_location()
+      )),
+    }
+  }
+
+  /// Assigns a value to the element designated by an index ASTree node.
+  /// Assigning past the end of an array is an error, arrays never grow implicitly.
+  ///
+  /// # Arguments
+  ///
+  /// * `value` - The value to assign.
+  /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
-  /// * A new ASTree instance.
-  pub fn new(token: Token) -> ASTree {
-    ASTree {
-      children: Vec::new(),
-      token: token,
+  /// * `Ok(())` if the assignment is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_index_assign(&self, value: RuntimeValue, ctx: &mut Context) -> Result<(), String> {
+    let target: RuntimeValue = self.children[0].eval(ctx)?;
+    let index: usize = self.eval_index_position(ctx)?;
+    match target {
+      RuntimeValue::ARRAY(elements) => {
+        let mut elements = elements.borrow_mut();
+        let len: usize = elements.len();
+        match elements.get_mut(index) {
+          Option::Some(element) => {
+            *element = value;
+            Ok(())
+          }
+          Option::None => Err(self.index_out_of_bounds(index, len)),
+        }
+      }
+      other => Err(format!(
+        "Cannot assign to an element of {:?}, at {}",
+        other,
+        self.token.get_location()
+      )),
     }
   }
 
-  /// Appends a child ASTree node to this node.
+  /// Evaluates a function definition ASTree node.
   ///
   /// # Arguments
   ///
-  /// * `child` - The child ASTree node to append.
-  pub fn append(&mut self, child: ASTree) {
-    self.children.push(Rc::new(child));
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_fn_def(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting 2 or more children from parser: function name, parameters..., body
+    let name = self.children[0].token.get_value();
+    let body = self.children.last().unwrap();
+    let mut params: Vec<String> = Vec::new();
+    for i in 1..(self.children.len() - 1) {
+      params.push(self.children[i].token.get_value().clone());
+    }
+    // body.clone() is cheap due to Rc
+    ctx.set_function(name.clone(), params, body.clone());
+    Ok(RuntimeValue::NULL)
   }
 
-  /// Returns a reference to the token type of this ASTree node.
+  /// Evaluates a lambda ASTree node into a function value.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
-  /// * A reference to the TokenType of this node's token.
-  pub fn get_type(&self) -> &TokenType {
-    self.token.get_type()
+  /// * `Ok(RuntimeValue::FUNCTION)` holding the lambda.
+  fn eval_lambda(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting 1 or more children from parser: parameters..., body
+    let body = self.children.last().unwrap();
+    let params: Vec<String> = self.children[..self.children.len() - 1]
+      .iter()
+      .map(|param| param.token.get_value().clone())
+      .collect();
+    Ok(RuntimeValue::FUNCTION(
+      ctx.new_function(params, body.clone()),
+    ))
   }
 
-  /// Evaluates a binary operation between two integer values.
+  /// Evaluates a call to a builtin function.
   ///
   /// # Arguments
   ///
-  /// * `val1` - The first integer value.
-  /// * `val2` - The second integer value.
+  /// * `builtin` - The builtin function to call.
+  /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
   /// * `Ok(RuntimeValue)` if evaluation is successful.
   /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_binop_ints(&self, val1: i32, val2: i32) -> Result<RuntimeValue, String> {
-    match self.token.get_value().as_str() {
-      "+" => Ok(RuntimeValue::INTEGER(val1 + val2)),
-      "-" => Ok(RuntimeValue::INTEGER(val1 - val2)),
-      "*" => Ok(RuntimeValue::INTEGER(val1 * val2)),
-      "/" => {
-        if val2 == 0 {
-          Err(format!(
-            "Division by zero error at position: {}",
-            self.token.get_position()
-          ))
-        } else {
-          Ok(RuntimeValue::INTEGER(val1 / val2))
-        }
-      }
-      "==" => Ok(RuntimeValue::BOOL(val1 == val2)),
-      "!=" => Ok(RuntimeValue::BOOL(val1 != val2)),
-      _ => Err(format!(
-        "Unsupported binary operator: '{}' between integers, at position: {}",
-        self.token.get_value(),
-        self.token.get_position()
-      )),
+  fn eval_builtin_call(&self, builtin: Builtin, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    let mut args: Vec<RuntimeValue> = Vec::new();
+    for child in &self.children {
+      args.push(child.eval(ctx)?);
     }
+    builtin(&args).map_err(|error| format!("{}, at {}", error, self.token.get_location()))
   }
 
-  /// Evaluates a binary operation between two boolean values.
+  /// Evaluates a call to a builtin function needing the context.
   ///
   /// # Arguments
   ///
-  /// * `val1` - The first boolean value.
-  /// * `val2` - The second boolean value.
+  /// * `builtin` - The builtin function to call.
+  /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
   /// * `Ok(RuntimeValue)` if evaluation is successful.
   /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_binop_bools(&self, val1: bool, val2: bool) -> Result<RuntimeValue, String> {
-    match self.token.get_value().as_str() {
-      "&&" => Ok(RuntimeValue::BOOL(val1 && val2)),
-      "||" => Ok(RuntimeValue::BOOL(val1 || val2)),
-      _ => Err(format!(
-        "Unsupported binary operator: '{}' between booleans, at position: {}",
-        self.token.get_value(),
-        self.token.get_position()
-      )),
+  fn eval_context_builtin_call(
+    &self,
+    builtin: ContextBuiltin,
+    ctx: &mut Context,
+  ) -> Result<RuntimeValue, String> {
+    let mut args: Vec<RuntimeValue> = Vec::new();
+    for child in &self.children {
+      args.push(child.eval(ctx)?);
     }
+    builtin(&args, ctx).map_err(|error| format!("{}, at {}", error, self.token.get_location()))
   }
 
-  /// Evaluates a binary operation between two string values.
+  /// Evaluates a function call ASTree node, calling either a user-defined function, a function
+  /// value held by a variable, or a builtin.
   ///
   /// # Arguments
   ///
-  /// * `val1` - The first string value.
-  /// * `val2` - The second string value.
+  /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
   /// * `Ok(RuntimeValue)` if evaluation is successful.
   /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_binop_strings(&self, val1: &String, val2: &String) -> Result<RuntimeValue, String> {
-    match self.token.get_value().as_str() {
-      "+" => Ok(RuntimeValue::STRING(format!("{}{}", val1, val2))),
-      "==" => Ok(RuntimeValue::BOOL(val1 == val2)),
-      "!=" => Ok(RuntimeValue::BOOL(val1 != val2)),
-      _ => Err(format!(
-        "Unsupported binary operator: '{}' between strings, at position: {}",
-        self.token.get_value(),
-        self.token.get_position()
-      )),
+  fn eval_fn_call(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting 0 or more children from parser: arguments...
+    let name: &String = self.token.get_value();
+    // A method call on a module, e.g. `m.sqrt(4)`, calls the module's function. Looking the
+    // receiver up doesn't evaluate anything, so it isn't evaluated twice otherwise.
+    if matches!(self.token.get_type(), TokenType::METHODCALL)
+      && matches!(self.children[0].get_type(), TokenType::IDENTIFIER)
+      && let Option::Some(RuntimeValue::MODULE(module)) =
+        ctx.get_variable(self.children[0].token.get_value())
+    {
+      let module: Rc<Module> = module.clone();
+      return self.call_module_function(&module, ctx);
     }
+    // A variable holding a function, such as a parameter, shadows functions of the same name
+    let func: Rc<Function> = match ctx.get_variable(name) {
+      Option::Some(RuntimeValue::FUNCTION(func)) => func.clone(),
+      _ => match ctx.get_function(name) {
+        Option::Some(func) => func,
+        Option::None => {
+          // User-defined functions shadow builtins of the same name
+          if let Some(builtin) = builtins::get_builtin(name) {
+            return self.eval_builtin_call(builtin, ctx);
+          }
+          if let Some(builtin) = builtins::get_context_builtin(name) {
+            return self.eval_context_builtin_call(builtin, ctx);
+          }
+          match name.as_str() {
+            "apply" => return self.eval_apply_builtin(ctx),
+            "min_by" | "max_by" => return self.eval_extremum_by_builtin(name, ctx),
+            _ => {}
+          }
+          return Err(format!(
+            "Attempted to call unset function: '{}', at {}",
+            name,
+            self.token.get_location()
+          ));
+        }
+      },
+    };
+
+    self.call_function(&func, &self.children, ctx)
   }
 
-  /// Evaluates a binary operation ASTree node.
+  /// Calls the function of a module named by a method call node, with the arguments following the
+  /// module. The module's functions are visible during the call, so that they can call each other.
+  ///
+  /// # Arguments
+  ///
+  /// * `module` - The module the function is taken from.
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` holding the value returned by the function.
+  /// * `Err(String)` if the module has no such function or an error occurs during evaluation.
+  fn call_module_function(
+    &self,
+    module: &Module,
+    ctx: &mut Context,
+  ) -> Result<RuntimeValue, String> {
+    // Expecting 1 or more children from parser: module, arguments...
+    let name: &String = self.token.get_value();
+    let func: Rc<Function> = module.get_function(name).ok_or_else(|| {
+      format!(
+        "Module '{}' has no function '{}', at {}",
+        module.get_name(),
+        name,
+        self.token.get_location()
+      )
+    })?;
+    // Arguments are evaluated before the module's functions become visible, like in any call
+    let mut args: Vec<RuntimeValue> = Vec::new();
+    for child in &self.children[1..] {
+      args.push(child.eval(ctx)?);
+    }
+    ctx.push_module_scope(module);
+    let result = self.invoke_function(&func, args, ctx);
+    ctx.pop_scope();
+    result
+  }
+
+  /// Evaluates an apply ASTree node, calling the function value its first child evaluates to.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
   /// * `Ok(RuntimeValue)` if evaluation is successful.
   /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_binary_op(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
-    // Expecting two children from parser: left and right operands
-    let param1: RuntimeValue = self.children[0].eval(ctx)?;
-    let param2: RuntimeValue = self.children[1].eval(ctx)?;
-
-    match (&param1, &param2) {
-      (RuntimeValue::INTEGER(val1), RuntimeValue::INTEGER(val2)) => {
-        self.eval_binop_ints(*val1, *val2)
-      }
-
-      (RuntimeValue::BOOL(val1), RuntimeValue::BOOL(val2)) => self.eval_binop_bools(*val1, *val2),
-
-      (RuntimeValue::STRING(val1), RuntimeValue::STRING(val2)) => {
-        self.eval_binop_strings(val1, val2)
-      }
-
-      _ => Err(format!(
-        "Type mismatch for binary operation {} at position: {}\n Left operand type: {:?}\n Right operand type: {:?}",
-        self.token.get_value(),
-        self.token.get_position(),
-        param1,
-        param2
+  fn eval_apply(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    // Expecting 1 or more children from parser: called expression, arguments...
+    match self.children[0].eval(ctx)? {
+      RuntimeValue::FUNCTION(func) => self.call_function(&func, &self.children[1..], ctx),
+      other => Err(format!(
+        "Cannot call {:?}, at {}",
+        other,
+        self.token.get_location()
       )),
     }
   }
 
-  /// Evaluates an if-statement ASTree node.
+  /// Evaluates a call to the `apply(fn, args)` builtin, calling a function value with the elements
+  /// of an array as its arguments.
+  ///
+  /// Unlike other builtins it calls back into user code, so it is evaluated here rather than in
+  /// the builtins module.
   ///
   /// # Arguments
   ///
@@ -189,123 +1990,242 @@ impl ASTree {
   ///
   /// # Returns
   ///
-  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Ok(RuntimeValue)` holding the value returned by the called function.
   /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_if(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
-    // Expecting two or three children from parser: condition, then-branch, else-branch (optional)
-    let condition_result: bool = match self.children[0].eval(ctx)? {
-      RuntimeValue::BOOL(val) => val,
+  fn eval_apply_builtin(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    if self.children.len() != 2 {
+      return Err(format!(
+        "Builtin 'apply' expects 2 argument(s), found {}, at {}",
+        self.children.len(),
+        self.token.get_location()
+      ));
+    }
+    let func: Rc<Function> = match self.children[0].eval(ctx)? {
+      RuntimeValue::FUNCTION(func) => func,
       other => {
         return Err(format!(
-          "If condition didn't evaluate to Boolean value, is: {:?}, at position {}",
+          "Builtin 'apply' expects a function as its first argument, found {:?}, at {}",
           other,
-          self.token.get_position()
+          self.token.get_location()
         ));
       }
     };
-
-    if condition_result {
-      // Then-branch
-      self.children[1].eval(ctx)
-    } else if self.children.len() == 3 {
-      // Else-branch
-      self.children[2].eval(ctx)
-    } else {
-      Ok(RuntimeValue::NULL)
-    }
+    let args: Vec<RuntimeValue> = match self.children[1].eval(ctx)? {
+      RuntimeValue::ARRAY(elements) => elements.borrow().clone(),
+      other => {
+        return Err(format!(
+          "Builtin 'apply' expects an array as its second argument, found {:?}, at {}",
+          other,
+          self.token.get_location()
+        ));
+      }
+    };
+    self.invoke_function(&func, args, ctx)
   }
 
-  /// Evaluates a while-loop ASTree node.
+  /// Evaluates a call to the `min_by(arr, key)` or `max_by(arr, key)` builtin, returning the
+  /// element of an array with the smallest or largest key, the key of an element being the result
+  /// of calling the key function on it.
+  ///
+  /// Keys are ordered like with `<`. When several elements share the smallest or largest key, the
+  /// first of them is returned. An empty array gives NULL.
   ///
   /// # Arguments
   ///
+  /// * `name` - The name of the builtin, `min_by` or `max_by`.
   /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
-  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
-  /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_while(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
-    // Expecting two children from parser: condition and body
-    while match self.children[0].eval(ctx)? {
-      RuntimeValue::BOOL(val) => val,
+  /// * `Ok(RuntimeValue)` holding the found element, or NULL for an empty array.
+  /// * `Err(String)` if the arguments are invalid, two keys can't be compared, or an error occurs
+  ///   in the key function.
+  fn eval_extremum_by_builtin(
+    &self,
+    name: &str,
+    ctx: &mut Context,
+  ) -> Result<RuntimeValue, String> {
+    if self.children.len() != 2 {
+      return Err(format!(
+        "Builtin '{}' expects 2 argument(s), found {}, at {}",
+        name,
+        self.children.len(),
+        self.token.get_location()
+      ));
+    }
+    let elements: Vec<RuntimeValue> = match self.children[0].eval(ctx)? {
+      RuntimeValue::ARRAY(elements) => elements.borrow().clone(),
       other => {
         return Err(format!(
-          "While condition didn't evaluate to Boolean value, is: {:?}, at position {}",
+          "Builtin '{}' expects an array as its first argument, found {:?}, at {}",
+          name,
           other,
-          self.token.get_position()
+          self.token.get_location()
         ));
       }
-    } {
-      self.children[1].eval(ctx)?;
+    };
+    let key_func: Rc<Function> = match self.children[1].eval(ctx)? {
+      RuntimeValue::FUNCTION(func) => func,
+      other => {
+        return Err(format!(
+          "Builtin '{}' expects a function as its second argument, found {:?}, at {}",
+          name,
+          other,
+          self.token.get_location()
+        ));
+      }
+    };
+    // The element replaces the best one so far only if its key is strictly better, so ties keep
+    // the first element
+    let wanted: Ordering = if name == "min_by" {
+      Ordering::Less
+    } else {
+      Ordering::Greater
+    };
+    let mut best: Option<(RuntimeValue, RuntimeValue)> = Option::None;
+    for element in elements {
+      let key: RuntimeValue = self.invoke_function(&key_func, vec![element.clone()], ctx)?;
+      best = match best {
+        Option::None => Option::Some((element, key)),
+        Option::Some((best_element, best_key)) => match key.compare(&best_key) {
+          Option::Some(ordering) if ordering == wanted => Option::Some((element, key)),
+          Option::Some(_) => Option::Some((best_element, best_key)),
+          Option::None => {
+            return Err(format!(
+              "Builtin '{}' cannot compare key {:?} with {:?}, at {}",
+              name,
+              key,
+              best_key,
+              self.token.get_location()
+            ));
+          }
+        },
+      };
     }
-    Ok(RuntimeValue::NULL)
+    Ok(best.map_or(RuntimeValue::NULL, |(element, _key)| element))
   }
 
-  /// Evaluates an assignment ASTree node.
+  /// Calls a user-defined function in a new frame, with the given argument expressions.
   ///
   /// # Arguments
   ///
+  /// * `func` - The function to call.
+  /// * `arg_nodes` - The argument expressions, evaluated in the caller's scope.
   /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
-  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
+  /// * `Ok(RuntimeValue)` holding the value of the function's last statement.
   /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_assign(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
-    // Expecting two children from parser: identifier and value
-    let name = self.children[0].token.get_value();
-    let value = self.children[1].eval(ctx)?;
-    ctx.set_variable(name.clone(), value);
-    Ok(RuntimeValue::NULL)
+  fn call_function(
+    &self,
+    func: &Function,
+    arg_nodes: &[Rc<ASTree>],
+    ctx: &mut Context,
+  ) -> Result<RuntimeValue, String> {
+    // Arguments are evaluated in the caller's scope, before any parameter is bound
+    let mut args: Vec<RuntimeValue> = Vec::new();
+    for child in arg_nodes {
+      args.push(child.eval(ctx)?);
+    }
+    self.invoke_function(func, args, ctx)
   }
 
-  /// Evaluates a function definition ASTree node.
+  /// Calls a user-defined function in a new frame, with already evaluated arguments.
   ///
   /// # Arguments
   ///
+  /// * `func` - The function to call.
+  /// * `args` - The values of the arguments, one per parameter.
   /// * `ctx` - The context for variable bindings during evaluation.
   ///
   /// # Returns
   ///
-  /// * `Ok(RuntimeValue::NULL)` if evaluation is successful.
-  /// * `Err(String)` if an error occurs during evaluation.
-  fn eval_fn_def(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
-    // Expecting 2 or more children from parser: function name, parameters..., body
-    let name = self.children[0].token.get_value();
-    let body = self.children.last().unwrap();
-    let mut params: Vec<String> = Vec::new();
-    for i in 1..(self.children.len() - 1) {
-      params.push(self.children[i].token.get_value().clone());
+  /// * `Ok(RuntimeValue)` holding the value of the function's last statement.
+  /// * `Err(String)` if the argument count doesn't match or an error occurs during evaluation.
+  fn invoke_function(
+    &self,
+    func: &Function,
+    args: Vec<RuntimeValue>,
+    ctx: &mut Context,
+  ) -> Result<RuntimeValue, String> {
+    if args.len() != func.get_params().len() {
+      return Err(format!(
+        "Function expects {} argument(s), found {}, at {}",
+        func.get_params().len(),
+        args.len(),
+        self.token.get_location()
+      ));
     }
-    // body.clone() is cheap due to Rc
-    ctx.set_function(name.clone(), params, body.clone());
-    Ok(RuntimeValue::NULL)
-  }
-
-  fn eval_fn_call(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
-    // Expecting 0 or more children from parser: arguments...
-    let func_body: Rc<ASTree> = match ctx.get_function_body(self.token.get_value()) {
-      Option::Some(func) => func,
-      Option::None => {
-        return Err(format!(
-          "Attempted to call unset function: '{}', at position: {}",
-          self.token.get_value(),
-          self.token.get_position()
-        ));
-      }
+    // A memoized function skips calls with the arguments of a cached call. Arguments without a
+    // key, such as functions, are never cached.
+    let cache_key: Option<Vec<ValueKey>> = if func.is_memoized() {
+      args.iter().map(RuntimeValue::to_key).collect()
+    } else {
+      Option::None
+    };
+    if let Option::Some(key) = &cache_key
+      && let Option::Some(value) = func.get_cached(key)
+    {
+      return Ok(value);
+    }
+    ctx
+      .enter_call()
+      .map_err(|error| format!("{}, at {}", error, self.token.get_location()))?;
+    // The callee only sees its parameters, its captured variables and the globals
+    let frame_name: String = match self.token.get_type() {
+      TokenType::CALL | TokenType::METHODCALL => format!("{}() frame", self.token.get_value()),
+      _ => format!("fn({}) frame", func.get_params().join(", ")),
     };
-    // Since a function body exists, parameters must also exist
-    // Rc to take ownership to avoid mutability issues
-    let func_params: Rc<Vec<String>> = ctx.get_function_params(self.token.get_value()).unwrap();
-    ctx.push_scope();
-    for (i, param_name) in func_params.iter().enumerate() {
-      let arg_value = self.children[i].eval(ctx)?;
+    ctx.push_frame(frame_name);
+    // Parameters are bound after the captured variables, so they shadow them
+    for (name, value) in func.get_captured() {
+      ctx.set_variable(name.clone(), value.clone());
+    }
+    for (param_name, arg_value) in func.get_params().iter().zip(args) {
       ctx.set_variable(param_name.clone(), arg_value);
     }
-    let result = func_body.eval(ctx);
-    dbg!(&ctx);
-    ctx.pop_scope();
+    // The body block evaluates to the value of its last statement, which is the function's
+    // return value unless a `return` ended the call. Statements such as assignments and loops
+    // evaluate to NULL.
+    let result = func
+      .get_body()
+      .eval(ctx)
+      .map(|value| ctx.take_return().unwrap_or(value));
+    ctx.pop_frame();
+    ctx.exit_call();
+    if let (Option::Some(key), Ok(value)) = (cache_key, &result) {
+      func.set_cached(key, value);
+    }
+    result
+  }
+
+  /// Evaluates the ASTree node while logging it and its result, indented by nesting depth.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_traced(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    let prefix: String = ctx.get_trace_prefix();
+    eprintln!(
+      "{}{:?} '{}' at {}",
+      prefix,
+      self.token.get_type(),
+      self.token.get_value(),
+      self.token.get_location()
+    );
+    ctx.enter_trace();
+    let result = self.eval_node(ctx);
+    ctx.exit_trace();
+    match &result {
+      Ok(value) => eprintln!("{}=> {:?}", prefix, value),
+      Err(error) => eprintln!("{}=> error: {}", prefix, error),
+    }
     result
   }
 
@@ -320,62 +2240,107 @@ impl ASTree {
   /// * `Ok(RuntimeValue)` if evaluation is successful.
   /// * `Err(String)` if an error occurs during evaluation.
   pub fn eval(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
+    let result = match ctx.count_step(self.token.get_line()) {
+      Err(error) => Err(format!("{}, at {}", error, self.token.get_location())),
+      Ok(()) if ctx.is_tracing() => self.eval_traced(ctx),
+      Ok(()) => self.eval_node(ctx),
+    };
+    if result.is_err() {
+      ctx.record_error_scopes();
+      ctx.record_error_location(self.token.get_line(), self.token.get_column());
+    }
+    result
+  }
+
+  /// Evaluates the ASTree node according to its token type.
+  ///
+  /// # Arguments
+  ///
+  /// * `ctx` - The context for variable bindings during evaluation.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(RuntimeValue)` if evaluation is successful.
+  /// * `Err(String)` if an error occurs during evaluation.
+  fn eval_node(&self, ctx: &mut Context) -> Result<RuntimeValue, String> {
     match self.token.get_type() {
-      TokenType::NUMERIC => match self.token.get_value().parse::<i32>() {
-        Ok(result) => return Ok(RuntimeValue::INTEGER(result)),
-        Err(error) => return Err(error.to_string()),
-      },
+      TokenType::NUMERIC => self.eval_numeric(),
 
       TokenType::STRING => Ok(RuntimeValue::STRING(self.token.get_value().clone())),
 
+      TokenType::BOOL => Ok(RuntimeValue::BOOL(self.token.get_value() == "true")),
+
       TokenType::BINARYOP => self.eval_binary_op(ctx),
 
-      TokenType::IDENTIFIER => {
-        if self.children.len() == 0 {
-          // Variable access
-          return match ctx.get_variable(self.token.get_value()) {
-            Option::Some(val) => Ok(val.clone()),
-            Option::None => Err(format!(
-              "Attempted to access unset identifier: '{}', at position: {}",
-              self.token.get_value(),
-              self.token.get_position()
-            )),
-          };
-        } else {
-          // Function call
-          self.eval_fn_call(ctx)
+      TokenType::NOT => self.eval_not(ctx),
+
+      TokenType::UNARYOP => self.eval_negate(ctx),
+
+      TokenType::IDENTIFIER => match ctx.get_variable(self.token.get_value()) {
+        Option::Some(val) => Ok(val.clone()),
+        // Referring to a function by name produces a function value
+        Option::None if let Some(func) = ctx.get_function(self.token.get_value()) => {
+          Ok(RuntimeValue::FUNCTION(func))
         }
-      }
+        Option::None => Err(format!(
+          "Attempted to access unset identifier: '{}', at {}",
+          self.token.get_value(),
+          self.token.get_location()
+        )),
+      },
+
+      TokenType::CALL | TokenType::METHODCALL => self.eval_fn_call(ctx),
+
+      TokenType::APPLY => self.eval_apply(ctx),
 
       TokenType::IF => self.eval_if(ctx),
 
       TokenType::WHILE => self.eval_while(ctx),
 
+      TokenType::FOR => self.eval_for(ctx),
+
+      TokenType::LOOP => self.eval_loop(ctx),
+
+      TokenType::REPEAT => self.eval_repeat(ctx),
+
       TokenType::ASSIGN => self.eval_assign(ctx),
 
+      TokenType::WALRUS => self.eval_walrus(ctx),
+
+      TokenType::GLOBAL => self.eval_global(ctx),
+
+      TokenType::CONST => self.eval_const(ctx),
+      TokenType::THROW => self.eval_throw(ctx),
+
+      TokenType::RETURN => self.eval_return(ctx),
+      TokenType::INCLUDE => self.eval_include(ctx),
+      TokenType::IMPORT => self.eval_import(ctx),
+      TokenType::BREAK | TokenType::CONTINUE => self.eval_loop_control(ctx),
+
       TokenType::FN => self.eval_fn_def(ctx),
 
+      TokenType::LAMBDA => self.eval_lambda(ctx),
+
+      TokenType::ARRAY => self.eval_array(ctx),
+
+      TokenType::INDEX => self.eval_index(ctx),
+
       TokenType::BLOCK(make_scope) => {
-        let mut last_value: RuntimeValue = RuntimeValue::NULL;
-        if *make_scope {
-          ctx.push_scope();
-        }
-        for child in &self.children {
-          last_value = child.eval(ctx)?;
+        if !*make_scope {
+          return self.eval_statements(ctx);
         }
-        if *make_scope {
-          dbg!(&ctx);
-          ctx.pop_scope();
-        }
-        Ok(last_value)
+        ctx.push_named_scope(self.token.get_value().clone());
+        // The scope is popped whether the statements succeed, fail, or are cut short by a change
+        // of control flow, so that no scope outlives its block
+        let result = self.eval_statements(ctx);
+        ctx.pop_scope();
+        result
       }
 
-      _ => {
-        return Err(format!(
-          "Unexpected TokenType evaluated: {:?}",
-          self.token.get_type()
-        ));
-      }
+      _ => Err(format!(
+        "Unexpected TokenType evaluated: {:?}",
+        self.token.get_type()
+      )),
     }
   }
 }
@@ -0,0 +1,888 @@
+//! Built-in functions available to every program.
+//!
+//! This module maps builtin names to their native implementations. Builtins are only looked up
+//! when no user-defined function of the same name is in scope, so user code can shadow them.
+
+use crate::ast::Integer;
+use crate::ast::RuntimeValue;
+use crate::context::Context;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Signature shared by all builtin functions, receiving the already evaluated arguments.
+pub type Builtin = fn(&[RuntimeValue]) -> Result<RuntimeValue, String>;
+
+/// Retrieves the builtin function registered under the given name.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin to retrieve.
+///
+/// # Returns
+///
+/// * `Some(Builtin)` if a builtin with this name exists, or `None` if it does not.
+pub fn get_builtin(name: &str) -> Option<Builtin> {
+  match name {
+    "len" => Some(len),
+    "is_empty" => Some(is_empty),
+    "first" => Some(first),
+    "last" => Some(last),
+    "contains" => Some(contains),
+    "to_upper" => Some(to_upper),
+    "to_lower" => Some(to_lower),
+    "trim" => Some(trim),
+    "trim_start" => Some(trim_start),
+    "trim_end" => Some(trim_end),
+    "replace" => Some(replace),
+    "eq_ignore_case" => Some(eq_ignore_case),
+    "chars" => Some(chars),
+    "ord" => Some(ord),
+    "chr" => Some(chr),
+    "to_hex" => Some(to_hex),
+    "to_bin" => Some(to_bin),
+    "push" => Some(push),
+    "sort" => Some(sort),
+    "concat" => Some(concat),
+    "join" => Some(join),
+    "zip" => Some(zip),
+    "sum" => Some(sum),
+    "product" => Some(product),
+    "gcd" => Some(gcd),
+    "lcm" => Some(lcm),
+    "pow_mod" => Some(pow_mod),
+    "count" => Some(count),
+    "enumerate" => Some(enumerate),
+    "clone" => Some(clone),
+    "pprint" => Some(pprint),
+    "memoize" => Some(memoize),
+    "assert_eq" => Some(assert_eq),
+    "assert_ne" => Some(assert_ne),
+    "is_null" => Some(is_null),
+    "is_number" => Some(is_number),
+    "is_string" => Some(is_string),
+    "is_bool" => Some(is_bool),
+    "is_array" => Some(is_array),
+    "parse_int" => Some(parse_int),
+    "parse_float" => Some(parse_float),
+    _ => None,
+  }
+}
+
+/// Signature of the builtins that also need the context, to read or bind variables.
+pub type ContextBuiltin = fn(&[RuntimeValue], &mut Context) -> Result<RuntimeValue, String>;
+
+/// Retrieves the builtin function needing the context registered under the given name.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin to retrieve.
+///
+/// # Returns
+///
+/// * `Some(ContextBuiltin)` if a builtin with this name exists, or `None` if it does not.
+pub fn get_context_builtin(name: &str) -> Option<ContextBuiltin> {
+  match name {
+    "env_get" => Some(env_get),
+    "env_set" => Some(env_set),
+    _ => None,
+  }
+}
+
+/// Checks that a builtin received the expected number of arguments.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin, used in the error message.
+/// * `args` - The arguments the builtin was called with.
+/// * `arity` - The expected number of arguments.
+///
+/// # Returns
+///
+/// * `Ok(())` if the argument count matches.
+/// * `Err(String)` if it does not.
+fn expect_arity(name: &str, args: &[RuntimeValue], arity: usize) -> Result<(), String> {
+  if args.len() != arity {
+    return Err(format!(
+      "Builtin '{}' expects {} argument(s), found {}",
+      name,
+      arity,
+      args.len()
+    ));
+  }
+  Ok(())
+}
+
+/// `len(x)`: returns the number of characters in a string or of elements in an array.
+fn len(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("len", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => Ok(RuntimeValue::INTEGER(val.chars().count() as Integer)),
+    RuntimeValue::ARRAY(elements) => Ok(RuntimeValue::INTEGER(elements.borrow().len() as Integer)),
+    other => Err(format!(
+      "Builtin 'len' expects a string or an array, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `is_empty(x)`: returns whether a string or an array has no characters or elements.
+fn is_empty(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("is_empty", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => Ok(RuntimeValue::BOOL(val.is_empty())),
+    RuntimeValue::ARRAY(elements) => Ok(RuntimeValue::BOOL(elements.borrow().is_empty())),
+    other => Err(format!(
+      "Builtin 'is_empty' expects a string or an array, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `first(x)`: returns the first character of a string or the first element of an array, which
+/// must not be empty.
+fn first(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("first", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => val
+      .chars()
+      .next()
+      .map(|character| RuntimeValue::STRING(character.to_string()))
+      .ok_or_else(|| "Builtin 'first' called on an empty string".to_string()),
+    RuntimeValue::ARRAY(elements) => elements
+      .borrow()
+      .first()
+      .cloned()
+      .ok_or_else(|| "Builtin 'first' called on an empty array".to_string()),
+    other => Err(format!(
+      "Builtin 'first' expects a string or an array, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `last(x)`: returns the last character of a string or the last element of an array, which
+/// must not be empty.
+fn last(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("last", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => val
+      .chars()
+      .next_back()
+      .map(|character| RuntimeValue::STRING(character.to_string()))
+      .ok_or_else(|| "Builtin 'last' called on an empty string".to_string()),
+    RuntimeValue::ARRAY(elements) => elements
+      .borrow()
+      .last()
+      .cloned()
+      .ok_or_else(|| "Builtin 'last' called on an empty array".to_string()),
+    other => Err(format!(
+      "Builtin 'last' expects a string or an array, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `contains(s, sub)`: returns whether `sub` is a substring of `s`.
+fn contains(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("contains", args, 2)?;
+  match (&args[0], &args[1]) {
+    (RuntimeValue::STRING(val), RuntimeValue::STRING(sub)) => {
+      Ok(RuntimeValue::BOOL(val.contains(sub.as_str())))
+    }
+    (val, sub) => Err(format!(
+      "Builtin 'contains' expects two strings, found {:?} and {:?}",
+      val, sub
+    )),
+  }
+}
+
+/// `to_upper(s)`: returns `s` with every character mapped to upper case, following Unicode.
+fn to_upper(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("to_upper", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => Ok(RuntimeValue::STRING(val.to_uppercase())),
+    other => Err(format!(
+      "Builtin 'to_upper' expects a string, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `to_lower(s)`: returns `s` with every character mapped to lower case, following Unicode.
+fn to_lower(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("to_lower", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => Ok(RuntimeValue::STRING(val.to_lowercase())),
+    other => Err(format!(
+      "Builtin 'to_lower' expects a string, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `trim(s)`: returns `s` without its leading and trailing whitespace.
+fn trim(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  trim_string("trim", args, str::trim)
+}
+
+/// `trim_start(s)`: returns `s` without its leading whitespace.
+fn trim_start(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  trim_string("trim_start", args, str::trim_start)
+}
+
+/// `trim_end(s)`: returns `s` without its trailing whitespace.
+fn trim_end(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  trim_string("trim_end", args, str::trim_end)
+}
+
+/// Shared implementation of the builtins removing whitespace from the ends of a string.
+///
+/// Whitespace follows Unicode, so it covers spaces, tabs and newlines among others.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin, used in error messages.
+/// * `args` - The arguments the builtin was called with.
+/// * `trim` - Removes the whitespace from the string.
+///
+/// # Returns
+///
+/// * `Ok(RuntimeValue::STRING)` holding the trimmed string.
+/// * `Err(String)` if the builtin didn't receive a single string.
+fn trim_string(
+  name: &str,
+  args: &[RuntimeValue],
+  trim: fn(&str) -> &str,
+) -> Result<RuntimeValue, String> {
+  expect_arity(name, args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => Ok(RuntimeValue::STRING(trim(val).to_string())),
+    other => Err(format!(
+      "Builtin '{}' expects a string, found {:?}",
+      name, other
+    )),
+  }
+}
+
+/// `replace(s, from, to)`: returns `s` with every non-overlapping occurrence of `from` replaced by
+/// `to`, scanning from the left. An empty `from` matches before every character and at the end,
+/// so `replace("ab", "", "-")` is `"-a-b-"`.
+fn replace(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("replace", args, 3)?;
+  match (&args[0], &args[1], &args[2]) {
+    (RuntimeValue::STRING(val), RuntimeValue::STRING(from), RuntimeValue::STRING(to)) => {
+      Ok(RuntimeValue::STRING(val.replace(from.as_str(), to)))
+    }
+    (a, b, c) => Err(format!(
+      "Builtin 'replace' expects three strings, found {:?}, {:?} and {:?}",
+      a, b, c
+    )),
+  }
+}
+
+/// `eq_ignore_case(a, b)`: returns whether `a` and `b` are equal once both are lower cased.
+fn eq_ignore_case(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("eq_ignore_case", args, 2)?;
+  match (&args[0], &args[1]) {
+    (RuntimeValue::STRING(val1), RuntimeValue::STRING(val2)) => Ok(RuntimeValue::BOOL(
+      val1.to_lowercase() == val2.to_lowercase(),
+    )),
+    (val1, val2) => Err(format!(
+      "Builtin 'eq_ignore_case' expects two strings, found {:?} and {:?}",
+      val1, val2
+    )),
+  }
+}
+
+/// `chars(s)`: returns an array of the characters of `s`, each as a single-character string.
+/// Characters are Unicode scalar values, so a multi-byte character is a single element.
+fn chars(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("chars", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => Ok(RuntimeValue::ARRAY(Rc::new(RefCell::new(
+      val
+        .chars()
+        .map(|character| RuntimeValue::STRING(character.to_string()))
+        .collect(),
+    )))),
+    other => Err(format!(
+      "Builtin 'chars' expects a string, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `ord(s)`: returns the Unicode scalar value of a single-character string.
+fn ord(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("ord", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => {
+      let mut chars = val.chars();
+      match (chars.next(), chars.next()) {
+        (Option::Some(character), Option::None) => Ok(RuntimeValue::INTEGER(character as Integer)),
+        _ => Err(format!(
+          "Builtin 'ord' expects a single character, found {} characters",
+          val.chars().count()
+        )),
+      }
+    }
+    other => Err(format!("Builtin 'ord' expects a string, found {:?}", other)),
+  }
+}
+
+/// `chr(n)`: returns the single-character string of a Unicode scalar value.
+fn chr(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("chr", args, 1)?;
+  match &args[0] {
+    RuntimeValue::INTEGER(val) => match u32::try_from(*val).ok().and_then(char::from_u32) {
+      Option::Some(character) => Ok(RuntimeValue::STRING(character.to_string())),
+      Option::None => Err(format!(
+        "Builtin 'chr' expects a Unicode scalar value, found {}",
+        val
+      )),
+    },
+    other => Err(format!(
+      "Builtin 'chr' expects an integer, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `to_hex(n)`: returns the integer `n` written in base 16, e.g. `"ff"` for 255.
+fn to_hex(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  format_integer("to_hex", args, 16)
+}
+
+/// `to_bin(n)`: returns the integer `n` written in base 2, e.g. `"101"` for 5.
+fn to_bin(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  format_integer("to_bin", args, 2)
+}
+
+/// Shared implementation of the builtins writing an integer in another base.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin, used in error messages.
+/// * `args` - The arguments the builtin was called with.
+/// * `radix` - The base to write the integer in.
+///
+/// # Returns
+///
+/// * `Ok(RuntimeValue::STRING)` holding the written integer.
+/// * `Err(String)` if the builtin didn't receive a single integer.
+fn format_integer(name: &str, args: &[RuntimeValue], radix: u32) -> Result<RuntimeValue, String> {
+  expect_arity(name, args, 1)?;
+  match &args[0] {
+    RuntimeValue::INTEGER(_) => Ok(RuntimeValue::STRING(args[0].format_radix(radix))),
+    other => Err(format!(
+      "Builtin '{}' expects an integer, found {:?}",
+      name, other
+    )),
+  }
+}
+
+/// `push(arr, x)`: appends `x` to the end of `arr` in place.
+fn push(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("push", args, 2)?;
+  match &args[0] {
+    RuntimeValue::ARRAY(elements) => {
+      elements.borrow_mut().push(args[1].clone());
+      Ok(RuntimeValue::NULL)
+    }
+    other => Err(format!(
+      "Builtin 'push' expects an array, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `sort(arr)`: returns a new array with the elements of `arr` in ascending order, `arr` itself
+/// is left unchanged. All elements must be comparable with each other.
+fn sort(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("sort", args, 1)?;
+  let mut sorted: Vec<RuntimeValue> = match &args[0] {
+    RuntimeValue::ARRAY(elements) => elements.borrow().clone(),
+    other => {
+      return Err(format!(
+        "Builtin 'sort' expects an array, found {:?}",
+        other
+      ));
+    }
+  };
+  // Comparability only depends on the value types, so checking every element against the first
+  // one guarantees every pair can be compared
+  if let Some(first) = sorted.first() {
+    for element in &sorted {
+      if first.compare(element).is_none() {
+        return Err(format!(
+          "Builtin 'sort' cannot compare {:?} with {:?}",
+          first, element
+        ));
+      }
+    }
+  }
+  sorted.sort_by(|val1, val2| val1.compare(val2).unwrap());
+  Ok(RuntimeValue::ARRAY(Rc::new(RefCell::new(sorted))))
+}
+
+/// `concat(a, b, ...)`: returns a new array with the elements of every array argument, in order.
+fn concat(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  let mut elements: Vec<RuntimeValue> = Vec::new();
+  for arg in args {
+    match arg {
+      RuntimeValue::ARRAY(val) => elements.extend(val.borrow().iter().cloned()),
+      other => {
+        return Err(format!(
+          "Builtin 'concat' expects arrays, found {:?}",
+          other
+        ));
+      }
+    }
+  }
+  Ok(RuntimeValue::ARRAY(Rc::new(RefCell::new(elements))))
+}
+
+/// `join(parts, separator)`: returns a string made of every element of `parts` separated by
+/// `separator`, elements that aren't strings are written as they are displayed.
+/// Pushing pieces to an array then joining them builds a string in linear time, where repeated
+/// `s = s + piece` copies the whole string on every step.
+fn join(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("join", args, 2)?;
+  match (&args[0], &args[1]) {
+    (RuntimeValue::ARRAY(elements), RuntimeValue::STRING(separator)) => {
+      let mut output: String = String::new();
+      for (i, element) in elements.borrow().iter().enumerate() {
+        if i > 0 {
+          output.push_str(separator);
+        }
+        output.push_str(&element.to_string());
+      }
+      Ok(RuntimeValue::STRING(output))
+    }
+    (parts, separator) => Err(format!(
+      "Builtin 'join' expects an array and a string, found {:?} and {:?}",
+      parts, separator
+    )),
+  }
+}
+
+/// `sum(arr)`: returns the sum of the numbers in `arr`, 0 for an empty array. Integers wrap around
+/// on overflow like `+`, and the sum is a float as soon as one of the numbers is.
+fn sum(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  fold_numbers("sum", args, 0, Integer::wrapping_add, |a, b| a + b)
+}
+
+/// `product(arr)`: returns the product of the numbers in `arr`, 1 for an empty array. Integers
+/// wrap around on overflow like `*`, and the product is a float as soon as one of the numbers is.
+fn product(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  fold_numbers("product", args, 1, Integer::wrapping_mul, |a, b| a * b)
+}
+
+/// Shared implementation of the builtins reducing an array of numbers to a single number. The
+/// result stays an integer while every element is one, and becomes a float from the first float
+/// element on.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin, used in error messages.
+/// * `args` - The arguments the builtin was called with.
+/// * `initial` - The result for an empty array.
+/// * `integer_operation` - Combines an integer result so far with the next integer element.
+/// * `float_operation` - Combines the result so far with the next element once either is a float.
+///
+/// # Returns
+///
+/// * `Ok(RuntimeValue::INTEGER)` or `Ok(RuntimeValue::FLOAT)` holding the result.
+/// * `Err(String)` if the argument isn't an array of numbers.
+fn fold_numbers(
+  name: &str,
+  args: &[RuntimeValue],
+  initial: Integer,
+  integer_operation: fn(Integer, Integer) -> Integer,
+  float_operation: fn(f64, f64) -> f64,
+) -> Result<RuntimeValue, String> {
+  expect_arity(name, args, 1)?;
+  let elements = match &args[0] {
+    RuntimeValue::ARRAY(elements) => elements.borrow(),
+    other => {
+      return Err(format!(
+        "Builtin '{}' expects an array, found {:?}",
+        name, other
+      ));
+    }
+  };
+  let mut result: RuntimeValue = RuntimeValue::INTEGER(initial);
+  for element in elements.iter() {
+    result = match (&result, element) {
+      (RuntimeValue::INTEGER(a), RuntimeValue::INTEGER(b)) => {
+        RuntimeValue::INTEGER(integer_operation(*a, *b))
+      }
+      (RuntimeValue::INTEGER(a), RuntimeValue::FLOAT(b)) => {
+        RuntimeValue::FLOAT(float_operation(*a as f64, *b))
+      }
+      (RuntimeValue::FLOAT(a), RuntimeValue::INTEGER(b)) => {
+        RuntimeValue::FLOAT(float_operation(*a, *b as f64))
+      }
+      (RuntimeValue::FLOAT(a), RuntimeValue::FLOAT(b)) => {
+        RuntimeValue::FLOAT(float_operation(*a, *b))
+      }
+      (_, other) => {
+        return Err(format!(
+          "Builtin '{}' expects numbers, found {:?}",
+          name, other
+        ));
+      }
+    };
+  }
+  Ok(result)
+}
+
+/// `gcd(a, b)`: returns the greatest common divisor of `a` and `b`, always non-negative, and 0
+/// when both are 0.
+fn gcd(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  let values: Vec<i128> = expect_integers("gcd", args, 2)?;
+  integer_result("gcd", gcd_i128(values[0], values[1]))
+}
+
+/// `lcm(a, b)`: returns the least common multiple of `a` and `b`, always non-negative, and 0 when
+/// either is 0.
+fn lcm(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  let values: Vec<i128> = expect_integers("lcm", args, 2)?;
+  if values[0] == 0 || values[1] == 0 {
+    return Ok(RuntimeValue::INTEGER(0));
+  }
+  // Dividing first keeps the intermediate value within the bounds of the result
+  let result: i128 = (values[0] / gcd_i128(values[0], values[1]) * values[1]).abs();
+  integer_result("lcm", result)
+}
+
+/// `pow_mod(base, exp, modulus)`: returns `base` to the power `exp`, modulo `modulus`, between 0
+/// and `modulus - 1`. The exponent can't be negative and the modulus must be positive.
+fn pow_mod(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  let values: Vec<i128> = expect_integers("pow_mod", args, 3)?;
+  let (base, mut exp, modulus) = (values[0], values[1], values[2]);
+  if exp < 0 {
+    return Err(format!(
+      "Builtin 'pow_mod' expects a non-negative exponent, found {}",
+      exp
+    ));
+  }
+  if modulus <= 0 {
+    return Err(format!(
+      "Builtin 'pow_mod' expects a positive modulus, found {}",
+      modulus
+    ));
+  }
+  // Both factors stay below the modulus, so their product always fits in an i128
+  let mut factor: i128 = base.rem_euclid(modulus);
+  let mut result: i128 = 1 % modulus;
+  while exp > 0 {
+    if exp % 2 == 1 {
+      result = result * factor % modulus;
+    }
+    factor = factor * factor % modulus;
+    exp /= 2;
+  }
+  integer_result("pow_mod", result)
+}
+
+/// Checks that a builtin received the expected number of integer arguments.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin, used in error messages.
+/// * `args` - The arguments the builtin was called with.
+/// * `arity` - The expected number of arguments.
+///
+/// # Returns
+///
+/// * `Ok(Vec<i128>)` holding the arguments, widened so that computing with them can't overflow.
+/// * `Err(String)` if the argument count doesn't match or an argument isn't an integer.
+fn expect_integers(name: &str, args: &[RuntimeValue], arity: usize) -> Result<Vec<i128>, String> {
+  expect_arity(name, args, arity)?;
+  args
+    .iter()
+    .map(|arg| match arg {
+      RuntimeValue::INTEGER(val) => Ok(*val as i128),
+      other => Err(format!(
+        "Builtin '{}' expects integers, found {:?}",
+        name, other
+      )),
+    })
+    .collect()
+}
+
+/// Converts the result of an integer builtin back to an integer value.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin, used in the error message.
+/// * `result` - The result, computed on widened integers.
+///
+/// # Returns
+///
+/// * `Ok(RuntimeValue::INTEGER)` holding the result.
+/// * `Err(String)` if the result doesn't fit in an integer.
+fn integer_result(name: &str, result: i128) -> Result<RuntimeValue, String> {
+  match Integer::try_from(result) {
+    Ok(val) => Ok(RuntimeValue::INTEGER(val)),
+    Err(_) => Err(format!(
+      "Builtin '{}' result {} doesn't fit in an integer",
+      name, result
+    )),
+  }
+}
+
+/// Computes the greatest common divisor of two integers with Euclid's algorithm.
+///
+/// # Arguments
+///
+/// * `a` - The first integer.
+/// * `b` - The second integer.
+///
+/// # Returns
+///
+/// * `i128` - The non-negative greatest common divisor.
+fn gcd_i128(a: i128, b: i128) -> i128 {
+  let (mut a, mut b) = (a.abs(), b.abs());
+  while b != 0 {
+    (a, b) = (b, a % b);
+  }
+  a
+}
+
+/// `count(arr, x)`: returns how many elements of `arr` are equal to `x`, following `==`.
+fn count(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("count", args, 2)?;
+  match &args[0] {
+    RuntimeValue::ARRAY(elements) => Ok(RuntimeValue::INTEGER(
+      elements
+        .borrow()
+        .iter()
+        .filter(|element| element.equals(&args[1]))
+        .count() as Integer,
+    )),
+    other => Err(format!(
+      "Builtin 'count' expects an array, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `zip(a, b)`: returns a new array of `[a[i], b[i]]` pairs. The result is as long as the shorter
+/// input, extra elements of the longer one are ignored.
+fn zip(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("zip", args, 2)?;
+  match (&args[0], &args[1]) {
+    (RuntimeValue::ARRAY(val1), RuntimeValue::ARRAY(val2)) => {
+      let pairs: Vec<RuntimeValue> = val1
+        .borrow()
+        .iter()
+        .zip(val2.borrow().iter())
+        .map(|(a, b)| RuntimeValue::ARRAY(Rc::new(RefCell::new(vec![a.clone(), b.clone()]))))
+        .collect();
+      Ok(RuntimeValue::ARRAY(Rc::new(RefCell::new(pairs))))
+    }
+    (a, b) => Err(format!(
+      "Builtin 'zip' expects two arrays, found {:?} and {:?}",
+      a, b
+    )),
+  }
+}
+
+/// `enumerate(arr)`: returns a new array of `[index, arr[index]]` pairs.
+fn enumerate(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("enumerate", args, 1)?;
+  match &args[0] {
+    RuntimeValue::ARRAY(elements) => {
+      let pairs: Vec<RuntimeValue> = elements
+        .borrow()
+        .iter()
+        .enumerate()
+        .map(|(i, element)| {
+          RuntimeValue::ARRAY(Rc::new(RefCell::new(vec![
+            RuntimeValue::INTEGER(i as Integer),
+            element.clone(),
+          ])))
+        })
+        .collect();
+      Ok(RuntimeValue::ARRAY(Rc::new(RefCell::new(pairs))))
+    }
+    other => Err(format!(
+      "Builtin 'enumerate' expects an array, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `clone(x)`: returns a deep copy of `x`, mutating the copy's arrays leaves `x` unchanged.
+fn clone(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("clone", args, 1)?;
+  Ok(args[0].deep_copy())
+}
+
+/// `pprint(x)`: prints `x` to stdout across several lines, with each element of a nested array on
+/// its own indented line.
+fn pprint(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("pprint", args, 1)?;
+  println!("{}", args[0].format_pretty());
+  Ok(RuntimeValue::NULL)
+}
+
+/// `memoize(f)`: returns a copy of the function `f` caching its results, so that calling it again
+/// with equal arguments returns the cached result without evaluating its body.
+fn memoize(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("memoize", args, 1)?;
+  match &args[0] {
+    RuntimeValue::FUNCTION(func) => Ok(RuntimeValue::FUNCTION(Rc::new(func.memoized()))),
+    other => Err(format!(
+      "Builtin 'memoize' expects a function, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `assert_eq(a, b)`: fails with both values when `a` and `b` aren't equal, following `==`.
+fn assert_eq(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("assert_eq", args, 2)?;
+  if !args[0].equals(&args[1]) {
+    return Err(format!("assertion failed: {} != {}", args[0], args[1]));
+  }
+  Ok(RuntimeValue::NULL)
+}
+
+/// `assert_ne(a, b)`: fails with both values when `a` and `b` are equal, following `==`.
+fn assert_ne(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("assert_ne", args, 2)?;
+  if args[0].equals(&args[1]) {
+    return Err(format!("assertion failed: {} == {}", args[0], args[1]));
+  }
+  Ok(RuntimeValue::NULL)
+}
+
+/// `is_null(x)`: returns whether `x` is NULL.
+fn is_null(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("is_null", args, 1)?;
+  Ok(RuntimeValue::BOOL(matches!(args[0], RuntimeValue::NULL)))
+}
+
+/// `is_number(x)`: returns whether `x` is a numeric value.
+fn is_number(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("is_number", args, 1)?;
+  Ok(RuntimeValue::BOOL(matches!(
+    args[0],
+    RuntimeValue::INTEGER(_) | RuntimeValue::FLOAT(_)
+  )))
+}
+
+/// `is_string(x)`: returns whether `x` is a string.
+fn is_string(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("is_string", args, 1)?;
+  Ok(RuntimeValue::BOOL(matches!(
+    args[0],
+    RuntimeValue::STRING(_)
+  )))
+}
+
+/// `is_bool(x)`: returns whether `x` is a boolean.
+fn is_bool(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("is_bool", args, 1)?;
+  Ok(RuntimeValue::BOOL(matches!(args[0], RuntimeValue::BOOL(_))))
+}
+
+/// `is_array(x)`: returns whether `x` is an array.
+fn is_array(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("is_array", args, 1)?;
+  Ok(RuntimeValue::BOOL(matches!(
+    args[0],
+    RuntimeValue::ARRAY(_)
+  )))
+}
+
+/// `parse_int(s)`: parses a string into an integer, returning NULL instead of erroring when the
+/// string isn't a valid integer.
+fn parse_int(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("parse_int", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => match val.parse::<Integer>() {
+      Ok(result) => Ok(RuntimeValue::INTEGER(result)),
+      Err(_) => Ok(RuntimeValue::NULL),
+    },
+    other => Err(format!(
+      "Builtin 'parse_int' expects a string, found {:?}",
+      other
+    )),
+  }
+}
+
+/// `parse_float(s)`: parses a string into a float, returning NULL instead of erroring when the
+/// string isn't a valid float. Integers such as `"3"` parse to a float too.
+fn parse_float(args: &[RuntimeValue]) -> Result<RuntimeValue, String> {
+  expect_arity("parse_float", args, 1)?;
+  match &args[0] {
+    RuntimeValue::STRING(val) => match val.parse::<f64>() {
+      Ok(result) => Ok(RuntimeValue::FLOAT(result)),
+      Err(_) => Ok(RuntimeValue::NULL),
+    },
+    other => Err(format!(
+      "Builtin 'parse_float' expects a string, found {:?}",
+      other
+    )),
+  }
+}
+
+/// Checks that a builtin received a valid variable name as its first argument, the same names
+/// the lexer accepts as identifiers.
+///
+/// # Arguments
+///
+/// * `name` - The name of the builtin, used in the error message.
+/// * `args` - The arguments the builtin was called with.
+///
+/// # Returns
+///
+/// * `Ok(&String)` holding the variable name.
+/// * `Err(String)` if the first argument isn't a string holding a valid identifier.
+fn expect_variable_name<'a>(name: &str, args: &'a [RuntimeValue]) -> Result<&'a String, String> {
+  match &args[0] {
+    RuntimeValue::STRING(val)
+      if val.starts_with(|character: char| character.is_alphabetic() || character == '_')
+        && val
+          .chars()
+          .all(|character| character.is_alphanumeric() || character == '_') =>
+    {
+      Ok(val)
+    }
+    other => Err(format!(
+      "Builtin '{}' expects a variable name, found {:?}",
+      name, other
+    )),
+  }
+}
+
+/// `env_get(name)`: returns the value of the variable named by the string `name`, or null if it
+/// is unset. The name is resolved like an identifier, from the current scope, its enclosing
+/// scopes up to the current function call, then the global scope.
+fn env_get(args: &[RuntimeValue], ctx: &mut Context) -> Result<RuntimeValue, String> {
+  expect_arity("env_get", args, 1)?;
+  let name: &String = expect_variable_name("env_get", args)?;
+  Ok(
+    ctx
+      .get_variable(name)
+      .cloned()
+      .unwrap_or(RuntimeValue::NULL),
+  )
+}
+
+/// `env_set(name, x)`: binds `x` to the variable named by the string `name`, exactly like the
+/// assignment `name = x` would. Constants cannot be reassigned.
+fn env_set(args: &[RuntimeValue], ctx: &mut Context) -> Result<RuntimeValue, String> {
+  expect_arity("env_set", args, 2)?;
+  let name: &String = expect_variable_name("env_set", args)?;
+  if ctx.is_constant(name) {
+    return Err(format!("Cannot reassign constant '{}'", name));
+  }
+  ctx.set_variable(name.clone(), args[1].clone());
+  Ok(RuntimeValue::NULL)
+}
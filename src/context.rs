@@ -5,18 +5,146 @@
 
 use crate::ast::ASTree;
 use crate::ast::RuntimeValue;
+use crate::ast::ValueKey;
 
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
 
+/// Number of evaluation steps between two checks of the timeout, reading the clock on every step
+/// would noticeably slow down evaluation.
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+
+/// Default maximum number of nested function calls.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// A pending change of control flow, raised by a `break` or `continue` statement and consumed by
+/// the loop it targets, or by a `return` statement and consumed by the enclosing function call.
+/// The label names the targeted loop, `None` targets the innermost one.
+#[derive(Debug, Clone)]
+pub enum ControlFlow {
+  /// Exits the targeted loop.
+  BREAK(Option<String>),
+  /// Skips to the next iteration of the targeted loop.
+  CONTINUE(Option<String>),
+  /// Exits the enclosing function call with the value, going through every loop on the way.
+  RETURN(RuntimeValue),
+}
+
+/// Represents a user-defined function.
 #[derive(Debug)]
-struct Function {
+pub struct Function {
   /// The parameter names of the function.
-  // This is an Rc to avoid mutability issues during function calls.
-  // (Cannot mutably set params within function's scope while iterating over immutable
-  // get_function_params -> &Vec<String>)
-  params: Rc<Vec<String>>,
+  params: Vec<String>,
+  /// The body of the function.
   body: Rc<ASTree>,
+  /// The local variables visible where the function was defined, copied at that time.
+  captured: HashMap<String, RuntimeValue>,
+  /// The results of earlier calls by their arguments, for a function returned by `memoize`.
+  /// `None` for any other function.
+  cache: Option<RefCell<HashMap<Vec<ValueKey>, RuntimeValue>>>,
+}
+
+impl Function {
+  /// Returns the parameter names of the function.
+  pub fn get_params(&self) -> &Vec<String> {
+    &self.params
+  }
+
+  /// Returns the body of the function.
+  pub fn get_body(&self) -> &ASTree {
+    &self.body
+  }
+
+  /// Returns the local variables captured when the function was defined.
+  pub fn get_captured(&self) -> &HashMap<String, RuntimeValue> {
+    &self.captured
+  }
+
+  /// Creates a copy of the function that caches its results, starting with an empty cache.
+  ///
+  /// # Returns
+  ///
+  /// * `Function` - The memoized function.
+  pub fn memoized(&self) -> Function {
+    Function {
+      params: self.params.clone(),
+      body: self.body.clone(),
+      captured: self.captured.clone(),
+      cache: Option::Some(RefCell::new(HashMap::new())),
+    }
+  }
+
+  /// Checks whether the function caches its results.
+  pub fn is_memoized(&self) -> bool {
+    self.cache.is_some()
+  }
+
+  /// Retrieves the cached result of an earlier call.
+  ///
+  /// # Arguments
+  ///
+  /// * `key` - The keys of the call's arguments.
+  ///
+  /// # Returns
+  ///
+  /// * `Some(RuntimeValue)` if a call with equal arguments was cached, `None` otherwise.
+  pub fn get_cached(&self, key: &[ValueKey]) -> Option<RuntimeValue> {
+    self
+      .cache
+      .as_ref()
+      .and_then(|cache| cache.borrow().get(key).map(RuntimeValue::deep_copy))
+  }
+
+  /// Caches the result of a call, if the function caches its results.
+  ///
+  /// # Arguments
+  ///
+  /// * `key` - The keys of the call's arguments.
+  /// * `value` - The result of the call.
+  pub fn set_cached(&self, key: Vec<ValueKey>, value: &RuntimeValue) {
+    if let Option::Some(cache) = &self.cache {
+      // Copied so that mutating a returned array never alters the cached result
+      cache.borrow_mut().insert(key, value.deep_copy());
+    }
+  }
+}
+
+/// Represents a module, the functions defined by a file imported under a name.
+#[derive(Debug)]
+pub struct Module {
+  /// The name the module was imported as.
+  name: String,
+  /// The functions defined at the top level of the imported file.
+  functions: HashMap<String, Rc<Function>>,
+}
+
+impl Module {
+  /// Creates a new `Module` instance.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name the module was imported as.
+  /// * `functions` - The functions defined at the top level of the imported file.
+  pub fn new(name: String, functions: HashMap<String, Rc<Function>>) -> Module {
+    Module { name, functions }
+  }
+
+  /// Returns the name the module was imported as.
+  pub fn get_name(&self) -> &String {
+    &self.name
+  }
+
+  /// Retrieves a function of the module by name.
+  pub fn get_function(&self, name: &String) -> Option<Rc<Function>> {
+    self.functions.get(name).cloned()
+  }
 }
 
 /// Represents the context for variable bindings during code interpretation.
@@ -26,7 +154,43 @@ pub struct Context {
   /// values.
   variables: Vec<HashMap<String, RuntimeValue>>,
   /// A stack of function scopes.
-  functions: Vec<HashMap<String, Function>>,
+  // Functions are behind an Rc so they can be shared with RuntimeValue::FUNCTION values, and
+  // held during a call while the context is mutated.
+  functions: Vec<HashMap<String, Rc<Function>>>,
+  /// The names of the constant variables of each scope, parallel to `variables`.
+  constants: Vec<HashSet<String>>,
+  /// The name of each scope, parallel to `variables`, e.g. "while_block" or "f() frame".
+  scope_names: Vec<String>,
+  /// The index of the first scope of each function call being evaluated. Variable lookups
+  /// inside a call stop at its first scope, then fall back to the global scope.
+  frames: Vec<usize>,
+  /// Number of spaces per nesting level in the trace output, tracing is disabled when `None`.
+  trace_indent: Option<usize>,
+  /// Current nesting depth of traced evaluations.
+  trace_depth: usize,
+  /// Maximum number of evaluation steps, unlimited when `None`.
+  max_steps: Option<u64>,
+  /// Number of evaluation steps performed so far.
+  steps: u64,
+  /// Number of evaluation steps performed per source line, profiling is disabled when `None`.
+  profile: Option<HashMap<usize, u64>>,
+  /// Maximum duration of the evaluation and the instant it expires, unlimited when `None`.
+  timeout: Option<(Duration, Instant)>,
+  /// Maximum number of nested function calls.
+  max_call_depth: usize,
+  /// Number of function calls currently being evaluated.
+  call_depth: usize,
+  /// The pending change of control flow, statements are skipped until a loop consumes it.
+  control_flow: Option<ControlFlow>,
+  /// Whether the scopes should be recorded when an error occurs.
+  dump_scopes_on_error: bool,
+  /// The scopes as they were where the first error occurred, formatted by `format_scopes`.
+  error_scopes: Option<String>,
+  /// The line and column of the node where the first error occurred.
+  error_location: Option<(usize, usize)>,
+  /// The canonical paths of the files being evaluated, each including file before the files it
+  /// includes. Empty while evaluating a program read from stdin.
+  source_files: Vec<PathBuf>,
 }
 
 impl Context {
@@ -34,25 +198,436 @@ impl Context {
   ///
   /// # Returns
   ///
-  /// * A new `Context` instance, starting with an empty global scope.
+  /// * A new `Context` instance, without any scope. Evaluating a program pushes its global scope.
   pub fn new() -> Context {
     Context {
       variables: Vec::new(),
       functions: Vec::new(),
+      constants: Vec::new(),
+      scope_names: Vec::new(),
+      frames: Vec::new(),
+      trace_indent: Option::None,
+      trace_depth: 0,
+      max_steps: Option::None,
+      steps: 0,
+      profile: Option::None,
+      timeout: Option::None,
+      max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+      call_depth: 0,
+      control_flow: Option::None,
+      dump_scopes_on_error: false,
+      error_scopes: Option::None,
+      error_location: Option::None,
+      source_files: Vec::new(),
     }
   }
 
-  /// Sets a variable in the current scope.
+  /// Sets the maximum number of evaluation steps, every evaluated ASTree node counts as one step.
+  ///
+  /// # Arguments
+  ///
+  /// * `max_steps` - The maximum number of steps, or `None` for no limit.
+  pub fn set_max_steps(&mut self, max_steps: Option<u64>) {
+    self.max_steps = max_steps;
+  }
+
+  /// Sets the maximum duration of the evaluation, starting now.
+  ///
+  /// # Arguments
+  ///
+  /// * `timeout` - The maximum duration, or `None` for no limit.
+  pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+    self.timeout = timeout.map(|duration| (duration, Instant::now() + duration));
+  }
+
+  /// Enables or disables counting evaluation steps per source line.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - Whether steps should be counted per line.
+  pub fn set_profiling(&mut self, enabled: bool) {
+    self.profile = if enabled {
+      Option::Some(HashMap::new())
+    } else {
+      Option::None
+    };
+  }
+
+  /// Enables or disables recording the scopes where an error occurs, see `take_error_scopes`.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - Whether the scopes should be recorded.
+  pub fn set_dump_scopes_on_error(&mut self, enabled: bool) {
+    self.dump_scopes_on_error = enabled;
+  }
+
+  /// Records the current scopes if recording is enabled and no error was recorded yet. Called as
+  /// an error propagates, the first call happens where the error occurred, before any scope is
+  /// popped.
+  pub fn record_error_scopes(&mut self) {
+    if self.dump_scopes_on_error && self.error_scopes.is_none() {
+      self.error_scopes = Option::Some(self.format_scopes());
+    }
+  }
+
+  /// Takes the scopes recorded where the first error occurred.
+  ///
+  /// # Returns
+  ///
+  /// * `Some(String)` holding the formatted scopes, or `None` if none were recorded.
+  pub fn take_error_scopes(&mut self) -> Option<String> {
+    self.error_scopes.take()
+  }
+
+  /// Records the location of a node whose evaluation failed, unless a location was recorded
+  /// already. Called as an error propagates, the first call happens where the error occurred.
+  ///
+  /// # Arguments
+  ///
+  /// * `line` - The line of the node, starting at 1.
+  /// * `column` - The column of the node, starting at 1.
+  pub fn record_error_location(&mut self, line: usize, column: usize) {
+    self.error_location.get_or_insert((line, column));
+  }
+
+  /// Replaces the recorded error location, for an error that is reported at another node than
+  /// the one it occurred at, e.g. an error in an included file is reported at the `include`.
+  ///
+  /// # Arguments
+  ///
+  /// * `line` - The line of the node, starting at 1.
+  /// * `column` - The column of the node, starting at 1.
+  pub fn set_error_location(&mut self, line: usize, column: usize) {
+    self.error_location = Option::Some((line, column));
+  }
+
+  /// Takes the location recorded where the first error occurred, so that the next error records
+  /// its own.
+  ///
+  /// # Returns
+  ///
+  /// * `Some((usize, usize))` holding the line and column, or `None` if none was recorded.
+  pub fn take_error_location(&mut self) -> Option<(usize, usize)> {
+    self.error_location.take()
+  }
+
+  /// Formats every scope, outermost first, one line per variable sorted by name so that the
+  /// output is the same on every run.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The formatted scopes.
+  pub fn format_scopes(&self) -> String {
+    let mut output: String = String::new();
+    for (i, scope) in self.variables.iter().enumerate() {
+      output.push_str(&format!("scope {} ({}):\n", i, self.scope_names[i]));
+      let sorted: BTreeMap<&String, &RuntimeValue> = scope.iter().collect();
+      for (name, value) in sorted {
+        let constant: &str = if self.constants[i].contains(name) {
+          "const "
+        } else {
+          ""
+        };
+        // Strings are quoted, as in arrays, to tell them apart from other values
+        let value: String = match value {
+          RuntimeValue::STRING(val) => format!("{:?}", val),
+          other => other.to_string(),
+        };
+        output.push_str(&format!("  {}{} = {}\n", constant, name, value));
+      }
+    }
+    output
+  }
+
+  /// Returns the number of evaluation steps performed per source line.
+  ///
+  /// # Returns
+  ///
+  /// * `Some(&HashMap<usize, u64>)` if profiling is enabled, or `None` if it is not.
+  pub fn get_profile(&self) -> Option<&HashMap<usize, u64>> {
+    self.profile.as_ref()
+  }
+
+  /// Counts one evaluation step and checks it against the step and time limits.
+  /// The timeout is only checked every `TIMEOUT_CHECK_INTERVAL` steps.
+  ///
+  /// # Arguments
+  ///
+  /// * `line` - The line of the evaluated token, the step is attributed to it when profiling.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(())` if evaluation may continue.
+  /// * `Err(String)` if a limit has been exceeded.
+  pub fn count_step(&mut self, line: usize) -> Result<(), String> {
+    self.steps += 1;
+    if let Some(profile) = &mut self.profile {
+      *profile.entry(line).or_insert(0) += 1;
+    }
+    if let Some(max_steps) = self.max_steps
+      && self.steps > max_steps
+    {
+      return Err(format!("Step limit of {} exceeded", max_steps));
+    }
+    if let Some((duration, deadline)) = self.timeout
+      && self.steps.is_multiple_of(TIMEOUT_CHECK_INTERVAL)
+      && Instant::now() > deadline
+    {
+      return Err(format!(
+        "Time limit of {} ms exceeded",
+        duration.as_millis()
+      ));
+    }
+    Ok(())
+  }
+
+  /// Sets the maximum number of nested function calls.
+  ///
+  /// # Arguments
+  ///
+  /// * `max_call_depth` - The maximum call depth.
+  pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+    self.max_call_depth = max_call_depth;
+  }
+
+  /// Enters a function call, checking it against the call depth limit. Must be paired with a
+  /// call to `exit_call` once the call has been evaluated, if successful.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(())` if the call may proceed.
+  /// * `Err(String)` if the maximum call depth has been exceeded.
+  pub fn enter_call(&mut self) -> Result<(), String> {
+    if self.call_depth >= self.max_call_depth {
+      return Err(format!(
+        "Maximum call depth of {} exceeded",
+        self.max_call_depth
+      ));
+    }
+    self.call_depth += 1;
+    Ok(())
+  }
+
+  /// Exits the function call entered last.
+  pub fn exit_call(&mut self) {
+    self.call_depth -= 1;
+  }
+
+  /// Raises a change of control flow, which stays pending until a loop consumes it.
+  ///
+  /// # Arguments
+  ///
+  /// * `control_flow` - The change of control flow.
+  pub fn set_control_flow(&mut self, control_flow: ControlFlow) {
+    self.control_flow = Some(control_flow);
+  }
+
+  /// Returns whether a change of control flow is pending, in which case the remaining statements
+  /// of the current block must be skipped.
+  pub fn has_control_flow(&self) -> bool {
+    self.control_flow.is_some()
+  }
+
+  /// Consumes the pending change of control flow if it targets the given loop.
+  ///
+  /// # Arguments
+  ///
+  /// * `label` - The label of the loop, if any.
+  ///
+  /// # Returns
+  ///
+  /// * `Some(ControlFlow)` if a pending change of control flow targets the loop, `None` if none
+  ///   is pending or it targets an outer loop.
+  pub fn take_control_flow(&mut self, label: Option<&String>) -> Option<ControlFlow> {
+    let targets_loop = match &self.control_flow {
+      Some(ControlFlow::BREAK(target)) | Some(ControlFlow::CONTINUE(target)) => {
+        target.is_none() || target.as_ref() == label
+      }
+      Some(ControlFlow::RETURN(_)) | Option::None => false,
+    };
+    if targets_loop {
+      self.control_flow.take()
+    } else {
+      Option::None
+    }
+  }
+
+  /// Consumes the pending change of control flow if it is a `return`, at the end of a function
+  /// call or of the program.
+  ///
+  /// # Returns
+  ///
+  /// * `Some(RuntimeValue)` holding the returned value, or `None` if no `return` is pending.
+  pub fn take_return(&mut self) -> Option<RuntimeValue> {
+    match self.control_flow.take() {
+      Some(ControlFlow::RETURN(value)) => Some(value),
+      other => {
+        self.control_flow = other;
+        Option::None
+      }
+    }
+  }
+
+  /// Enables or disables tracing of every evaluated ASTree node.
+  ///
+  /// # Arguments
+  ///
+  /// * `indent` - The number of spaces per nesting level in the trace output, or `None` to
+  ///   disable tracing.
+  pub fn set_trace(&mut self, indent: Option<usize>) {
+    self.trace_indent = indent;
+  }
+
+  /// Returns whether evaluated ASTree nodes should be traced.
+  pub fn is_tracing(&self) -> bool {
+    self.trace_indent.is_some()
+  }
+
+  /// Returns the indentation for a trace line at the current nesting depth.
+  pub fn get_trace_prefix(&self) -> String {
+    " ".repeat(self.trace_indent.unwrap_or(0) * self.trace_depth)
+  }
+
+  /// Increases the trace nesting depth, before evaluating a node's children.
+  pub fn enter_trace(&mut self) {
+    self.trace_depth += 1;
+  }
+
+  /// Decreases the trace nesting depth, after evaluating a node's children.
+  pub fn exit_trace(&mut self) {
+    self.trace_depth -= 1;
+  }
+
+  /// Records that evaluation enters a file, the main program's file or an included one.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The canonical path of the file.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(())` if the file isn't already being evaluated.
+  /// * `Err(String)` if it is, since including it again would never end.
+  pub fn enter_source_file(&mut self, path: PathBuf) -> Result<(), String> {
+    if self.source_files.contains(&path) {
+      return Err(format!(
+        "Circular include or import of '{}'",
+        path.display()
+      ));
+    }
+    self.source_files.push(path);
+    Ok(())
+  }
+
+  /// Records that evaluation leaves the innermost file, after all its statements were evaluated.
+  pub fn exit_source_file(&mut self) {
+    self.source_files.pop();
+  }
+
+  /// Resolves the path of an included file relative to the directory of the including file, or to
+  /// the working directory for a program read from stdin.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The path as written in the include statement.
+  ///
+  /// # Returns
+  ///
+  /// * `PathBuf` - The resolved path, unchanged if it is absolute.
+  pub fn resolve_include(&self, path: &str) -> PathBuf {
+    match self.source_files.last().and_then(|file| file.parent()) {
+      Option::Some(directory) => directory.join(path),
+      Option::None => Path::new(path).to_path_buf(),
+    }
+  }
+
+  /// Sets a variable, updating it in the innermost scope of the current function call that
+  /// already has it, e.g. a counter assigned in a loop's body, or creating it in the current scope
+  /// otherwise. Inside a function call, a global variable is shadowed rather than updated, only
+  /// `global` assigns it.
   ///
   /// # Arguments
   ///
   /// * `name` - The name of the variable to set.
   /// * `value` - The value to assign to the variable.
   pub fn set_variable(&mut self, name: String, value: RuntimeValue) {
+    let frame_start: usize = self.frames.last().copied().unwrap_or(0);
+    let scope: usize = (frame_start..self.variables.len())
+      .rev()
+      .find(|&i| self.variables[i].contains_key(&name))
+      .unwrap_or(self.variables.len() - 1);
+    self.variables[scope].insert(name, value);
+  }
+
+  /// Sets a variable in the global scope, regardless of the current scope.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the variable to set.
+  /// * `value` - The value to assign to the variable.
+  pub fn set_global_variable(&mut self, name: String, value: RuntimeValue) {
+    self.variables.first_mut().unwrap().insert(name, value);
+  }
+
+  /// Sets a constant variable in the current scope, which cannot be reassigned afterwards.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the constant to set.
+  /// * `value` - The value of the constant.
+  pub fn set_constant(&mut self, name: String, value: RuntimeValue) {
+    self.constants.last_mut().unwrap().insert(name.clone());
     self.variables.last_mut().unwrap().insert(name, value);
   }
 
-  /// Retrieves the value of a variable from the current scope or any enclosing scopes.
+  /// Checks whether a name resolves to a constant, from the current scope or any enclosing
+  /// scopes.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the variable to check.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the innermost variable with this name is a constant, `false` otherwise.
+  pub fn is_constant(&self, name: &String) -> bool {
+    for i in self.visible_scopes() {
+      if self.variables[i].contains_key(name) {
+        return self.constants[i].contains(name);
+      }
+    }
+    false
+  }
+
+  /// Checks whether a name is a constant of the global scope.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the variable to check.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the global variable with this name is a constant, `false` otherwise.
+  pub fn is_global_constant(&self, name: &String) -> bool {
+    self
+      .constants
+      .first()
+      .is_some_and(|constants| constants.contains(name))
+  }
+
+  /// Returns the indices of the scopes whose variables are visible, innermost first. Inside a
+  /// function call, these are the scopes of the call followed by the global scope, the scopes
+  /// of its caller are hidden.
+  fn visible_scopes(&self) -> impl Iterator<Item = usize> {
+    let frame_start: usize = self.frames.last().copied().unwrap_or(0);
+    (frame_start..self.variables.len())
+      .rev()
+      .chain((frame_start > 0).then_some(0))
+  }
+
+  /// Retrieves the value of a variable from the current scope or any enclosing scopes, up to the
+  /// current function call, or from the global scope.
   ///
   /// # Arguments
   ///
@@ -62,7 +637,7 @@ impl Context {
   ///
   /// * `Some(&RuntimeValue)` if the variable is found, or `None` if it is not found.
   pub fn get_variable(&self, name: &String) -> Option<&RuntimeValue> {
-    for i in (0..self.variables.len()).rev() {
+    for i in self.visible_scopes() {
       if let Some(value) = self.variables[i].get(name) {
         return Some(value);
       }
@@ -77,61 +652,288 @@ impl Context {
   /// * `name` - The name of the function to set.
   /// * `func_ast` - The AST representation of the function.
   pub fn set_function(&mut self, name: String, params: Vec<String>, body: Rc<ASTree>) {
-    self.functions.last_mut().unwrap().insert(
-      name,
-      Function {
-        params: Rc::new(params),
-        body,
-      },
-    );
+    let func: Rc<Function> = self.new_function(params, body);
+    self.functions.last_mut().unwrap().insert(name, func);
   }
 
-  /// Retrieves the AST representation of a function body from the current scope or any enclosing
-  /// scopes.
+  /// Creates a function capturing the local variables currently visible, named functions and
+  /// lambdas alike.
+  ///
+  /// Captured variables are copied when the function is created, later assignments to them are
+  /// not seen by the function. Arrays are shared by reference, as with any other copy of a value.
+  /// Global variables are not captured, the function always sees their current value.
   ///
   /// # Arguments
   ///
-  /// * `name` - The name of the function to retrieve.
+  /// * `params` - The parameter names of the function.
+  /// * `body` - The body of the function.
   ///
   /// # Returns
   ///
-  /// * `Some(&ASTree)` if the function is found, or `None` if it is not found.
-  pub fn get_function_body(&self, name: &String) -> Option<Rc<ASTree>> {
-    for i in (0..self.functions.len()).rev() {
-      if let Some(func_ast) = self.functions[i].get(name) {
-        return Some(func_ast.body.clone());
+  /// * `Rc<Function>` - The new function.
+  pub fn new_function(&self, params: Vec<String>, body: Rc<ASTree>) -> Rc<Function> {
+    let mut captured: HashMap<String, RuntimeValue> = HashMap::new();
+    // Only the local scopes of the current function call are visible. Inner scopes come last, so
+    // their variables overwrite the ones they shadow.
+    let frame_start: usize = self.frames.last().copied().unwrap_or(0).max(1);
+    for scope in &self.variables[frame_start.min(self.variables.len())..] {
+      for (name, value) in scope {
+        captured.insert(name.clone(), value.clone());
       }
     }
-    Option::None
+    Rc::new(Function {
+      params,
+      body,
+      captured,
+      cache: Option::None,
+    })
   }
 
-  /// Retrieves the parameter names of a function from the current scope or any enclosing scopes.
+  /// Retrieves a function from the current scope or any enclosing scopes.
+  /// Unlike variables, functions of a caller stay visible inside a function call, so that a
+  /// function defined inside another one can call itself.
   ///
   /// # Arguments
   ///
-  /// * `name` - The name of the function to retrieve parameters for.
+  /// * `name` - The name of the function to retrieve.
   ///
   /// # Returns
   ///
-  /// * `Some(&Vec<String>)` if the function is found, or `None` if it is not found.
-  pub fn get_function_params(&self, name: &String) -> Option<Rc<Vec<String>>> {
+  /// * `Some(Rc<Function>)` if the function is found, or `None` if it is not found.
+  pub fn get_function(&self, name: &String) -> Option<Rc<Function>> {
     for i in (0..self.functions.len()).rev() {
       if let Some(func) = self.functions[i].get(name) {
-        return Some(func.params.clone());
+        return Some(func.clone());
       }
     }
     Option::None
   }
 
+  /// Creates an independent copy of this context, to evaluate code without affecting it.
+  ///
+  /// Arrays are deep-copied, so mutating an array in the fork never shows in this context. Each
+  /// variable gets its own copy, so an array shared by reference between several variables here
+  /// is no longer shared between them in the fork. Functions are immutable and are shared. The
+  /// fork keeps the trace and limit settings, but starts with fresh step and call counters and
+  /// without profiling.
+  ///
+  /// # Returns
+  ///
+  /// * A new `Context` instance with a copy of every scope.
+  pub fn fork(&self) -> Context {
+    Context {
+      variables: self
+        .variables
+        .iter()
+        .map(|scope| {
+          scope
+            .iter()
+            .map(|(name, value)| (name.clone(), value.deep_copy()))
+            .collect()
+        })
+        .collect(),
+      functions: self.functions.clone(),
+      constants: self.constants.clone(),
+      scope_names: self.scope_names.clone(),
+      frames: self.frames.clone(),
+      trace_indent: self.trace_indent,
+      trace_depth: self.trace_depth,
+      max_steps: self.max_steps,
+      steps: 0,
+      profile: Option::None,
+      timeout: self.timeout,
+      max_call_depth: self.max_call_depth,
+      call_depth: 0,
+      control_flow: Option::None,
+      dump_scopes_on_error: self.dump_scopes_on_error,
+      error_scopes: Option::None,
+      error_location: Option::None,
+      source_files: self.source_files.clone(),
+    }
+  }
+
+  /// Merges selected bindings of a fork back into the current scope of this context, as if they
+  /// had been assigned here. Each name brings back the variable or function it resolves to in
+  /// the fork, a constant stays constant.
+  ///
+  /// Merged values are not copied again, arrays merged from the fork are shared with it.
+  ///
+  /// # Arguments
+  ///
+  /// * `fork` - The fork to take the bindings from.
+  /// * `names` - The names of the bindings to merge.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(())` if every binding was merged.
+  /// * `Err(String)` if this context has no scope to merge into, or if a name would overwrite a
+  ///   constant of this context, nothing is merged then.
+  pub fn merge(&mut self, fork: Context, names: &[String]) -> Result<(), String> {
+    if self.variables.is_empty() {
+      return Err("Cannot merge into a context without a scope".to_string());
+    }
+    if let Some(name) = names.iter().find(|name| self.is_constant(name)) {
+      return Err(format!("Cannot merge over constant '{}'", name));
+    }
+    for name in names {
+      if let Some(value) = fork.get_variable(name) {
+        if fork.is_constant(name) {
+          self.set_constant(name.clone(), value.clone());
+        } else {
+          self.set_variable(name.clone(), value.clone());
+        }
+      }
+      if let Some(func) = fork.get_function(name) {
+        self
+          .functions
+          .last_mut()
+          .unwrap()
+          .insert(name.clone(), func);
+      }
+    }
+    Ok(())
+  }
+
   /// Pushes a new scope onto the stack.
-  pub fn push_scope(&mut self) {
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the scope, shown when the scopes are dumped.
+  pub fn push_named_scope(&mut self, name: String) {
     self.variables.push(HashMap::new());
     self.functions.push(HashMap::new());
+    self.constants.push(HashSet::new());
+    self.scope_names.push(name);
+  }
+
+  /// Pushes a scope holding the functions of a module, so that they can call each other while one
+  /// of them is called from outside the module. Pop it with `pop_scope`.
+  ///
+  /// # Arguments
+  ///
+  /// * `module` - The module whose functions are made visible.
+  pub fn push_module_scope(&mut self, module: &Module) {
+    self.push_named_scope(format!("{} module", module.get_name()));
+    self
+      .functions
+      .last_mut()
+      .unwrap()
+      .extend(module.functions.clone());
+  }
+
+  /// Removes the functions defined in the current scope, to move them into a module.
+  ///
+  /// # Returns
+  ///
+  /// * `HashMap<String, Rc<Function>>` - The functions of the current scope, by name.
+  pub fn take_scope_functions(&mut self) -> HashMap<String, Rc<Function>> {
+    std::mem::take(self.functions.last_mut().unwrap())
   }
 
   /// Pops the current scope from the stack.
   pub fn pop_scope(&mut self) {
     self.variables.pop();
     self.functions.pop();
+    self.constants.pop();
+    self.scope_names.pop();
+  }
+
+  /// Gets the number of scopes on the stack.
+  ///
+  /// # Returns
+  ///
+  /// * `usize` - The number of scopes, 0 outside of any evaluation.
+  pub fn get_scope_depth(&self) -> usize {
+    self.variables.len()
+  }
+
+  /// Pushes the first scope of a function call, hiding the variables of the caller's scopes
+  /// until the matching `pop_frame`. Global variables stay visible.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the scope, shown when the scopes are dumped.
+  pub fn push_frame(&mut self, name: String) {
+    self.frames.push(self.variables.len());
+    self.push_named_scope(name);
+  }
+
+  /// Pops the first scope of a function call, making the caller's scopes visible again.
+  pub fn pop_frame(&mut self) {
+    self.pop_scope();
+    self.frames.pop();
+  }
+}
+
+impl Default for Context {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Returns the displayed value of a variable, if it is defined.
+  fn variable(context: &Context, name: &str) -> Option<String> {
+    context
+      .get_variable(&name.to_string())
+      .map(|value| value.to_string())
+  }
+
+  #[test]
+  fn get_variable_finds_a_variable_of_the_only_scope() {
+    let mut context: Context = Context::new();
+    context.push_named_scope("global".to_string());
+    context.set_variable("x".to_string(), RuntimeValue::INTEGER(1));
+
+    assert_eq!(variable(&context, "x"), Some("1".to_string()));
+    assert_eq!(variable(&context, "y"), None);
+  }
+
+  #[test]
+  fn get_variable_looks_through_nested_scopes_innermost_first() {
+    let mut context: Context = Context::new();
+    context.push_named_scope("global".to_string());
+    context.set_variable("x".to_string(), RuntimeValue::INTEGER(1));
+    context.set_variable("y".to_string(), RuntimeValue::INTEGER(2));
+    context.push_named_scope("block".to_string());
+    context.push_named_scope("inner_block".to_string());
+    // Unlike set_variable, which would update the outer `y`, set_constant shadows it
+    context.set_constant("y".to_string(), RuntimeValue::INTEGER(3));
+
+    assert_eq!(variable(&context, "x"), Some("1".to_string()));
+    assert_eq!(variable(&context, "y"), Some("3".to_string()));
+    context.pop_scope();
+    assert_eq!(variable(&context, "y"), Some("2".to_string()));
+  }
+
+  #[test]
+  fn get_variable_of_an_empty_context_is_none() {
+    assert_eq!(variable(&Context::new(), "x"), None);
+  }
+
+  #[test]
+  fn merge_brings_back_forked_assignments() {
+    let mut context: Context = Context::new();
+    context.push_named_scope("global".to_string());
+    context.set_variable("x".to_string(), RuntimeValue::INTEGER(1));
+    let mut fork: Context = context.fork();
+    fork.set_variable("x".to_string(), RuntimeValue::INTEGER(2));
+    fork.set_variable("y".to_string(), RuntimeValue::INTEGER(3));
+
+    assert_eq!(context.merge(fork, &["y".to_string()]), Ok(()));
+    assert_eq!(variable(&context, "x"), Some("1".to_string()));
+    assert_eq!(variable(&context, "y"), Some("3".to_string()));
+  }
+
+  #[test]
+  fn merge_into_a_context_without_a_scope_fails() {
+    let mut fork: Context = Context::new();
+    fork.push_named_scope("global".to_string());
+    fork.set_variable("x".to_string(), RuntimeValue::INTEGER(1));
+
+    assert!(Context::new().merge(fork, &["x".to_string()]).is_err());
   }
 }
@@ -0,0 +1,109 @@
+//! Helpers for reporting locations in the source code.
+//!
+//! Tokens store their line and column, which the lexer, parser and evaluator include in their
+//! error messages as `line 3, column 12`. Runtime errors carry the location the evaluator recorded,
+//! while this module reads it back from lexing and parsing messages, to format errors like
+//! compilers do.
+
+/// An error message, with the location in the source it refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceError {
+  /// The message, as produced by the stage that failed.
+  message: String,
+  /// The line and column the error refers to, if the message names them.
+  location: Option<(usize, usize)>,
+}
+
+impl SourceError {
+  /// Wraps an error message, taking its location from the last `line N, column M` it contains,
+  /// the way the lexer, parser and evaluator word their messages.
+  ///
+  /// # Arguments
+  ///
+  /// * `message` - The error message.
+  ///
+  /// # Returns
+  ///
+  /// * `SourceError` - The message with its location, if it names one.
+  pub fn new(message: String) -> SourceError {
+    let location: Option<(usize, usize)> =
+      message.rmatch_indices("line ").find_map(|(index, word)| {
+        let (line, rest) = Self::read_number(&message[index + word.len()..])?;
+        let (column, _rest) = Self::read_number(rest.strip_prefix(", column ")?)?;
+        Option::Some((line, column))
+      });
+    SourceError { message, location }
+  }
+
+  /// Wraps an error message with a known location, such as the one the evaluator records in the
+  /// `Context`, rather than reading it from the message, whose values may look like a location.
+  ///
+  /// # Arguments
+  ///
+  /// * `message` - The error message.
+  /// * `location` - The line and column the error refers to, if any.
+  ///
+  /// # Returns
+  ///
+  /// * `SourceError` - The message with the given location.
+  pub fn with_location(message: String, location: Option<(usize, usize)>) -> SourceError {
+    SourceError { message, location }
+  }
+
+  /// Reads the number a text starts with.
+  ///
+  /// # Arguments
+  ///
+  /// * `text` - The text to read.
+  ///
+  /// # Returns
+  ///
+  /// * `Some((usize, &str))` holding the number and the text after it, or `None` if the text
+  ///   doesn't start with a digit.
+  fn read_number(text: &str) -> Option<(usize, &str)> {
+    let end: usize = text
+      .find(|character: char| !character.is_ascii_digit())
+      .unwrap_or(text.len());
+    let number: usize = text[..end].parse().ok()?;
+    Option::Some((number, &text[end..]))
+  }
+
+  /// Returns the error message.
+  pub fn get_message(&self) -> &String {
+    &self.message
+  }
+
+  /// Returns the line the error refers to, starting at 1, if any.
+  pub fn get_line(&self) -> Option<usize> {
+    self.location.map(|(line, _column)| line)
+  }
+
+  /// Returns the column the error refers to, starting at 1, if any.
+  pub fn get_column(&self) -> Option<usize> {
+    self.location.map(|(_line, column)| column)
+  }
+
+  /// Formats the error as a single line, prefixed by its location like compilers do, e.g.
+  /// `main.txt:3:5: Error during parsing: ...`. Line breaks in the message become `;`.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the source, such as its path.
+  /// * `stage` - The stage that failed, e.g. `parsing`.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The formatted diagnostic, without a trailing newline.
+  pub fn format(&self, name: &str, stage: &str) -> String {
+    let location: String = match self.location {
+      Option::Some((line, column)) => format!("{}:{}:{}", name, line, column),
+      Option::None => name.to_string(),
+    };
+    format!(
+      "{}: Error during {}: {}",
+      location,
+      stage,
+      self.message.replace('\n', ";")
+    )
+  }
+}
@@ -3,10 +3,17 @@
 //! This module provides a `Lexer` struct that can tokenize input strings into a sequence of
 //! tokens.
 
+use crate::ast::Integer;
 use crate::token::Token;
 use crate::token::TokenType;
 
+/// Every symbol the lexer emits a token for.
+const SYMBOLS: [&str; 16] = [
+  "+", "-", "*", "/", "%", "==", "!=", "<", "<=", ">", ">=", "&&", "||", "=", "=>", "!",
+];
+
 /// Represents the current state of the lexer.
+#[derive(Clone)]
 enum LexerState {
   /// Parsing a number.
   NUMBER,
@@ -14,6 +21,8 @@ enum LexerState {
   IDENTIFIER,
   /// Parsing a string literal
   STRING,
+  /// Parsing a raw string literal, in which backslashes are never escapes
+  RAWSTRING,
   /// Parsing a symbol
   SYMBOL,
   /// No current state.
@@ -21,23 +30,30 @@ enum LexerState {
 }
 
 /// A lexer for tokenizing input strings.
+///
+/// Cloning a lexer snapshots its position in the input, like cloning a `Parser`.
+#[derive(Clone)]
 pub struct Lexer {
-  input: String,
+  /// The characters of the input, indexed directly so that each one is read in constant time.
+  input: Vec<char>,
   index: usize,
   state: LexerState,
   current_token_string: String,
   current_token_position: usize,
+  /// The positions at which each line of the input starts, to locate tokens by line and column.
+  line_starts: Vec<usize>,
 }
 
 impl Lexer {
   /// Creates a new `Lexer` instance.
   pub fn new() -> Lexer {
     Lexer {
-      input: String::new(),
+      input: Vec::new(),
       index: 0,
       state: LexerState::NONE,
       current_token_string: String::new(),
       current_token_position: 0,
+      line_starts: vec![0],
     }
   }
 
@@ -47,34 +63,99 @@ impl Lexer {
   ///
   /// * `input` - The input string to be tokenized.
   pub fn set_input(&mut self, input: String) {
-    self.input = input;
+    self.input = input.chars().collect();
     self.index = 0;
     self.state = LexerState::NONE;
     self.current_token_string.clear();
     self.current_token_position = 0;
+    self.line_starts = vec![0];
+  }
+
+  /// Converts a position in the input to its line and column.
+  ///
+  /// # Arguments
+  ///
+  /// * `position` - The index of the character in the input.
+  ///
+  /// # Returns
+  ///
+  /// * `(usize, usize)` - The line and column of the character, both starting at 1.
+  fn locate(&self, position: usize) -> (usize, usize) {
+    // The first line starts at 0, so at least one line start is at or before any position
+    let line: usize = self
+      .line_starts
+      .partition_point(|&line_start| line_start <= position);
+    (line, position - self.line_starts[line - 1] + 1)
+  }
+
+  /// Formats a position in the input the way error messages word it, e.g. `line 3, column 12`.
+  ///
+  /// # Arguments
+  ///
+  /// * `position` - The index of the character in the input.
+  fn format_location(&self, position: usize) -> String {
+    let (line, column) = self.locate(position);
+    format!("line {}, column {}", line, column)
+  }
+
+  /// Creates a token starting at a position in the input, with its line and column.
+  ///
+  /// # Arguments
+  ///
+  /// * `token_type` - The type of the token.
+  /// * `value` - The string value of the token.
+  /// * `position` - The index of the token's first character in the input.
+  fn new_token(&self, token_type: TokenType, value: String, position: usize) -> Token {
+    let (line, column) = self.locate(position);
+    Token::new(token_type, value, position, line, column)
   }
 
   /// Checks if a character is a valid symbol.
   fn is_valid_symbol(character: char) -> bool {
-    match character {
-      '+' | '-' | '*' | '/' | '=' | '!' | '&' | '|' => true,
-      _ => false,
-    }
+    matches!(
+      character,
+      '+' | '-' | '*' | '/' | '%' | '=' | '!' | '&' | '|' | '<' | '>'
+    )
   }
 
-  /// Emits a number token based on the current token string.
+  /// Emits a number token based on the current token string, with its `_` digit separators
+  /// removed. Float literals keep their decimal point.
   ///
   /// # Arguments
   ///
   /// * `tokens` - A mutable reference to the vector of tokens.
-  fn emit_number_token(&mut self, tokens: &mut Vec<Token>) {
-    tokens.push(Token::new(
-      TokenType::NUMERIC,
-      self.current_token_string.clone(),
-      self.current_token_position,
-    ));
+  ///
+  /// # Returns
+  ///
+  /// * `Result<(), String>` - Ok if the separators are placed between digits, or an error message.
+  fn emit_number_token(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
+    // The literal always starts with a digit and a decimal point is always followed by one, so a
+    // separator is misplaced when it ends the literal, follows another separator or precedes the
+    // decimal point
+    if self.current_token_string.ends_with('_')
+      || self.current_token_string.contains("__")
+      || self.current_token_string.contains("_.")
+    {
+      return Err(format!(
+        "Misplaced '_' in numeric literal '{}' at {}, separators must be between digits",
+        self.current_token_string,
+        self.format_location(self.current_token_position)
+      ));
+    }
+    let value: String = self.current_token_string.replace('_', "");
+    // Reported here rather than when the literal is evaluated, so that no program containing it
+    // starts running
+    if !value.contains('.') && value.parse::<Integer>().is_err() {
+      return Err(format!(
+        "Integer literal '{}' at {} doesn't fit in an integer",
+        self.current_token_string,
+        self.format_location(self.current_token_position)
+      ));
+    }
+    tokens.push(self.new_token(TokenType::NUMERIC, value, self.current_token_position));
     self.current_token_string.clear();
     self.state = LexerState::NONE;
+    Ok(())
   }
 
   /// Emits an identifier token based on the current token string.
@@ -87,11 +168,25 @@ impl Lexer {
       "if" => TokenType::IF,
       "while" => TokenType::WHILE,
       "for" => TokenType::FOR,
+      "loop" => TokenType::LOOP,
+      "repeat" => TokenType::REPEAT,
       "else" => TokenType::ELSE,
       "fn" => TokenType::FN,
+      "not" => TokenType::NOT,
+      "in" => TokenType::BINARYOP,
+      "true" | "false" => TokenType::BOOL,
+      "global" => TokenType::GLOBAL,
+      "const" => TokenType::CONST,
+      "throw" => TokenType::THROW,
+      "return" => TokenType::RETURN,
+      "include" => TokenType::INCLUDE,
+      "import" => TokenType::IMPORT,
+      "as" => TokenType::AS,
+      "break" => TokenType::BREAK,
+      "continue" => TokenType::CONTINUE,
       _ => TokenType::IDENTIFIER,
     };
-    tokens.push(Token::new(
+    tokens.push(self.new_token(
       token_type,
       self.current_token_string.clone(),
       self.current_token_position,
@@ -106,7 +201,7 @@ impl Lexer {
   ///
   /// * `tokens` - A mutable reference to the vector of tokens.
   fn emit_string_token(&mut self, tokens: &mut Vec<Token>) {
-    tokens.push(Token::new(
+    tokens.push(self.new_token(
       TokenType::STRING,
       self.current_token_string.clone(),
       self.current_token_position,
@@ -126,16 +221,21 @@ impl Lexer {
   /// * `Result<(), String>` - A result indicating success or an error message.
   fn emit_symbol_token(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
     let token_type = match self.current_token_string.as_str() {
-      "+" | "-" | "*" | "/" | "==" | "!=" | "&&" | "||" => TokenType::BINARYOP,
+      "+" | "-" | "*" | "/" | "%" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "&&" | "||" => {
+        TokenType::BINARYOP
+      }
       "=" => TokenType::ASSIGN,
+      "=>" => TokenType::ARROW,
+      "!" => TokenType::NOT,
       _ => {
         return Err(format!(
-          "Invalid symbol '{}' at position {}",
-          self.current_token_string, self.current_token_position
+          "Invalid symbol '{}' at {}",
+          self.current_token_string,
+          self.format_location(self.current_token_position)
         ));
       }
     };
-    tokens.push(Token::new(
+    tokens.push(self.new_token(
       token_type,
       self.current_token_string.clone(),
       self.current_token_position,
@@ -145,6 +245,65 @@ impl Lexer {
     Ok(())
   }
 
+  /// Skips a line comment, from its `//` to the end of the line. The line break is left to be
+  /// skipped as whitespace.
+  fn skip_line_comment(&mut self) {
+    while self.index < self.input.len() && self.input.get(self.index).copied() != Some('\n') {
+      self.index += 1;
+    }
+  }
+
+  /// Skips a block comment, from its `/*` to the first `*/` after it, possibly spanning several
+  /// lines. Block comments don't nest.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(())` once the closing `*/` is skipped.
+  /// * `Err(String)` if the input ends before the comment is closed.
+  fn skip_block_comment(&mut self) -> Result<(), String> {
+    let start: usize = self.index;
+    self.index += 2;
+    while self.index < self.input.len() {
+      if self.input.get(self.index).copied() == Some('*')
+        && self.input.get(self.index + 1).copied() == Some('/')
+      {
+        self.index += 2;
+        return Ok(());
+      }
+      self.index += 1;
+    }
+    Err(format!(
+      "Unterminated block comment starting at {}",
+      self.format_location(start)
+    ))
+  }
+
+  /// Decodes the escape sequence starting at the current backslash of a string literal.
+  ///
+  /// # Returns
+  ///
+  /// * `Ok(char)` - The character the escape sequence stands for.
+  /// * `Err(String)` if the escaped character isn't one of `"`, `\\`, `n`, `t` or `r`, or the input
+  ///   ends after the backslash.
+  fn read_escape_sequence(&self) -> Result<char, String> {
+    match self.input.get(self.index + 1).copied() {
+      Option::Some('"') => Ok('"'),
+      Option::Some('\\') => Ok('\\'),
+      Option::Some('n') => Ok('\n'),
+      Option::Some('t') => Ok('\t'),
+      Option::Some('r') => Ok('\r'),
+      Option::Some(other) => Err(format!(
+        "Unknown escape sequence '\\{}' at {}",
+        other,
+        self.format_location(self.index)
+      )),
+      Option::None => Err(format!(
+        "Unterminated string literal starting at {}",
+        self.format_location(self.current_token_position)
+      )),
+    }
+  }
+
   /// Tokenizes the input string into a vector of tokens.
   ///
   /// # Returns
@@ -152,17 +311,34 @@ impl Lexer {
   /// * `Result<Vec<Token>, String>` - A result containing a vector of tokens or an error message.
   pub fn tokenize(&mut self) -> Result<Vec<Token>, String> {
     let mut tokens: Vec<Token> = Vec::new();
+    self.line_starts = vec![0];
+    for (index, &character) in self.input.iter().enumerate() {
+      if character == '\n' {
+        self.line_starts.push(index + 1);
+      }
+    }
 
     while self.index < self.input.len() {
-      let character: char = self.input.chars().nth(self.index).unwrap();
+      let character: char = self.input[self.index];
       match self.state {
         LexerState::NONE => {
-          if character.is_ascii_digit() {
+          if character == 'r' && self.input.get(self.index + 1).copied() == Some('"') {
+            // The token's position is the 'r' prefix, its content starts after the quote
+            self.state = LexerState::RAWSTRING;
+            self.current_token_position = self.index;
+            self.index += 2;
+          } else if character.is_ascii_digit() {
             self.state = LexerState::NUMBER;
             self.current_token_position = self.index;
-          } else if character.is_ascii_alphabetic() || character == '_' {
+          } else if character.is_alphabetic() || character == '_' {
             self.state = LexerState::IDENTIFIER;
             self.current_token_position = self.index;
+          } else if character == '/' && self.input.get(self.index + 1).copied() == Some('/') {
+            // Comments are checked before symbols, since no symbol contains "//" or "/*" but one
+            // would start at the first '/'
+            self.skip_line_comment();
+          } else if character == '/' && self.input.get(self.index + 1).copied() == Some('*') {
+            self.skip_block_comment()?;
           } else if Self::is_valid_symbol(character) {
             self.state = LexerState::SYMBOL;
             self.current_token_position = self.index;
@@ -171,34 +347,61 @@ impl Lexer {
           } else {
             match character {
               '"' => {
-                self.index += 1;
+                // The token's position is the opening quote, not the first character inside it
                 self.state = LexerState::STRING;
                 self.current_token_position = self.index;
+                self.index += 1;
               }
               '{' => {
-                tokens.push(Token::new(TokenType::LBRACE, "{".to_string(), self.index));
+                tokens.push(self.new_token(TokenType::LBRACE, "{".to_string(), self.index));
                 self.index += 1;
               }
               '}' => {
-                tokens.push(Token::new(TokenType::RBRACE, "}".to_string(), self.index));
+                tokens.push(self.new_token(TokenType::RBRACE, "}".to_string(), self.index));
+                self.index += 1;
+              }
+              '[' => {
+                tokens.push(self.new_token(TokenType::LBRACKET, "[".to_string(), self.index));
+                self.index += 1;
+              }
+              ']' => {
+                tokens.push(self.new_token(TokenType::RBRACKET, "]".to_string(), self.index));
                 self.index += 1;
               }
               '(' => {
-                tokens.push(Token::new(TokenType::LPAREN, "(".to_string(), self.index));
+                tokens.push(self.new_token(TokenType::LPAREN, "(".to_string(), self.index));
                 self.index += 1;
               }
               ')' => {
-                tokens.push(Token::new(TokenType::RPAREN, ")".to_string(), self.index));
+                tokens.push(self.new_token(TokenType::RPAREN, ")".to_string(), self.index));
                 self.index += 1;
               }
               ',' => {
-                tokens.push(Token::new(TokenType::COMMA, ",".to_string(), self.index));
+                tokens.push(self.new_token(TokenType::COMMA, ",".to_string(), self.index));
+                self.index += 1;
+              }
+              '.' => {
+                tokens.push(self.new_token(TokenType::DOT, ".".to_string(), self.index));
+                self.index += 1;
+              }
+              ':' if self.input.get(self.index + 1).copied() == Some('=') => {
+                tokens.push(self.new_token(TokenType::WALRUS, ":=".to_string(), self.index));
+                self.index += 2;
+              }
+              ':' => {
+                tokens.push(self.new_token(TokenType::COLON, ":".to_string(), self.index));
+                self.index += 1;
+              }
+              ';' => {
+                tokens.push(self.new_token(TokenType::SEMICOLON, ";".to_string(), self.index));
                 self.index += 1;
               }
               _ => {
+                // Escaped so that control characters can't garble the message
                 return Err(format!(
-                  "Invalid character '{}' at position {}",
-                  character, self.index
+                  "Invalid character '{}' at {}",
+                  character.escape_debug(),
+                  self.format_location(self.index)
                 ));
               }
             }
@@ -206,8 +409,22 @@ impl Lexer {
         }
 
         LexerState::NUMBER => {
-          if !character.is_ascii_digit() {
-            self.emit_number_token(&mut tokens);
+          // A dot followed by a digit is a decimal point, any other dot is a separate token
+          let next_is_digit: bool = self
+            .input
+            .get(self.index + 1)
+            .is_some_and(|next| next.is_ascii_digit());
+          if character == '.' && next_is_digit {
+            if self.current_token_string.contains('.') {
+              return Err(format!(
+                "Unexpected second decimal point in numeric literal at {}",
+                self.format_location(self.index)
+              ));
+            }
+            self.current_token_string.push(character);
+            self.index += 1;
+          } else if !(character.is_ascii_digit() || character == '_') {
+            self.emit_number_token(&mut tokens)?;
           } else {
             self.current_token_string.push(character);
             self.index += 1;
@@ -215,7 +432,7 @@ impl Lexer {
         }
 
         LexerState::IDENTIFIER => {
-          if !(character.is_ascii_alphanumeric() || character == '_') {
+          if !(character.is_alphanumeric() || character == '_') {
             self.emit_identifier_token(&mut tokens);
           } else {
             self.current_token_string.push(character);
@@ -227,14 +444,32 @@ impl Lexer {
           if character == '"' {
             self.emit_string_token(&mut tokens);
             self.index += 1;
+          } else if character == '\\' {
+            let escaped: char = self.read_escape_sequence()?;
+            self.current_token_string.push(escaped);
+            self.index += 2;
           } else {
             self.current_token_string.push(character);
             self.index += 1;
           }
         }
 
+        LexerState::RAWSTRING => {
+          if character == '"' {
+            self.emit_string_token(&mut tokens);
+          } else {
+            self.current_token_string.push(character);
+          }
+          self.index += 1;
+        }
+
         LexerState::SYMBOL => {
-          if !Self::is_valid_symbol(character) {
+          // Symbols are as long as possible, so `!=` is one symbol but `=!` is `=` followed by
+          // `!`
+          let candidate: String = format!("{}{}", self.current_token_string, character);
+          if !Self::is_valid_symbol(character)
+            || !SYMBOLS.iter().any(|symbol| symbol.starts_with(&candidate))
+          {
             self.emit_symbol_token(&mut tokens)?;
           } else {
             self.current_token_string.push(character);
@@ -246,12 +481,12 @@ impl Lexer {
 
     if !self.current_token_string.is_empty() {
       match self.state {
-        LexerState::NUMBER => self.emit_number_token(&mut tokens),
+        LexerState::NUMBER => self.emit_number_token(&mut tokens)?,
         LexerState::IDENTIFIER => self.emit_identifier_token(&mut tokens),
-        LexerState::STRING => {
+        LexerState::STRING | LexerState::RAWSTRING => {
           return Err(format!(
-            "Unterminated string literal starting at position {}",
-            self.current_token_position
+            "Unterminated string literal starting at {}",
+            self.format_location(self.current_token_position)
           ));
         }
         LexerState::SYMBOL => self.emit_symbol_token(&mut tokens)?,
@@ -261,7 +496,202 @@ impl Lexer {
 
     // DO NOT REMOVE THIS EOF TOKEN - PARSER EXPECTS IT TO BE PRESENT
     // AT THE END OF THE TOKEN STREAM, INFINITY LOOPS WILL OCCUR OTHERWISE
-    tokens.push(Token::new(TokenType::EOF, String::new(), self.index));
+    tokens.push(self.new_token(TokenType::EOF, String::new(), self.index));
     Ok(tokens)
   }
 }
+
+impl Default for Lexer {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Tokenizes an input that is expected to be valid.
+  fn tokenize(input: &str) -> Vec<Token> {
+    let mut lexer: Lexer = Lexer::new();
+    lexer.set_input(input.to_string());
+    lexer.tokenize().expect("The input should lex")
+  }
+
+  /// Returns the types of the tokens, in order.
+  fn types(tokens: &[Token]) -> Vec<TokenType> {
+    tokens
+      .iter()
+      .map(|token| token.get_type().clone())
+      .collect()
+  }
+
+  /// Returns the position of the only token with the given value.
+  fn position_of(tokens: &[Token], value: &str) -> usize {
+    let matching: Vec<&Token> = tokens
+      .iter()
+      .filter(|token| token.get_value() == value)
+      .collect();
+    assert_eq!(matching.len(), 1, "Expected a single '{value}' token");
+    *matching[0].get_position()
+  }
+
+  #[test]
+  fn multi_char_operators_start_at_their_first_character() {
+    let tokens: Vec<Token> = tokenize("if (a == b && c <= d) { e := f }");
+    assert_eq!(position_of(&tokens, "=="), 6);
+    assert_eq!(position_of(&tokens, "&&"), 11);
+    assert_eq!(position_of(&tokens, "<="), 16);
+    assert_eq!(position_of(&tokens, ":="), 26);
+
+    let tokens: Vec<Token> = tokenize("g = x => x");
+    assert_eq!(position_of(&tokens, "="), 2);
+    assert_eq!(position_of(&tokens, "=>"), 6);
+  }
+
+  #[test]
+  fn operators_at_end_of_input_start_at_their_first_character() {
+    assert_eq!(position_of(&tokenize("a =="), "=="), 2);
+    assert_eq!(position_of(&tokenize("a &&"), "&&"), 2);
+  }
+
+  #[test]
+  fn keywords_mid_line_start_at_their_first_character() {
+    let tokens: Vec<Token> = tokenize("x = 1; while (x) { break } else { return x }");
+    assert_eq!(position_of(&tokens, "while"), 7);
+    assert_eq!(position_of(&tokens, "break"), 19);
+    assert_eq!(position_of(&tokens, "else"), 27);
+    assert_eq!(position_of(&tokens, "return"), 34);
+  }
+
+  #[test]
+  fn decimal_point_is_part_of_a_float() {
+    let tokens: Vec<Token> = tokenize("3.14");
+    assert_eq!(types(&tokens), [TokenType::NUMERIC, TokenType::EOF]);
+    assert_eq!(tokens[0].get_value(), "3.14");
+  }
+
+  #[test]
+  fn standalone_dot_is_a_token() {
+    let tokens: Vec<Token> = tokenize("x . y");
+    assert_eq!(
+      types(&tokens),
+      [
+        TokenType::IDENTIFIER,
+        TokenType::DOT,
+        TokenType::IDENTIFIER,
+        TokenType::EOF
+      ]
+    );
+  }
+
+  #[test]
+  fn dot_not_followed_by_a_digit_ends_a_number() {
+    let tokens: Vec<Token> = tokenize("3.len");
+    assert_eq!(
+      types(&tokens),
+      [
+        TokenType::NUMERIC,
+        TokenType::DOT,
+        TokenType::IDENTIFIER,
+        TokenType::EOF
+      ]
+    );
+    assert_eq!(tokens[0].get_value(), "3");
+  }
+
+  #[test]
+  fn line_and_column_count_characters_across_lines() {
+    let tokens: Vec<Token> = tokenize("é = 1\n  ü = \"日本\" + x\nz");
+    let location_of = |value: &str| {
+      let token: &Token = &tokens[tokens
+        .iter()
+        .position(|token| token.get_value() == value)
+        .expect("The token should exist")];
+      (token.get_line(), token.get_column())
+    };
+    assert_eq!(location_of("é"), (1, 1));
+    assert_eq!(location_of("1"), (1, 5));
+    assert_eq!(location_of("ü"), (2, 3));
+    assert_eq!(location_of("日本"), (2, 7));
+    assert_eq!(location_of("x"), (2, 14));
+    assert_eq!(location_of("z"), (3, 1));
+  }
+
+  #[test]
+  fn arrow_is_lexed_only_without_a_space() {
+    let tokens: Vec<Token> = tokenize("a => b");
+    assert_eq!(
+      types(&tokens),
+      [
+        TokenType::IDENTIFIER,
+        TokenType::ARROW,
+        TokenType::IDENTIFIER,
+        TokenType::EOF
+      ]
+    );
+
+    let tokens: Vec<Token> = tokenize("a = > b");
+    assert_eq!(
+      types(&tokens),
+      [
+        TokenType::IDENTIFIER,
+        TokenType::ASSIGN,
+        TokenType::BINARYOP,
+        TokenType::IDENTIFIER,
+        TokenType::EOF
+      ]
+    );
+    assert_eq!(tokens[2].get_value(), ">");
+
+    // `>=` is a comparison, not an arrow
+    let tokens: Vec<Token> = tokenize("a >= b");
+    assert_eq!(tokens[1].get_type(), &TokenType::BINARYOP);
+    assert_eq!(tokens[1].get_value(), ">=");
+  }
+
+  #[test]
+  fn integer_literal_out_of_range_is_a_lexing_error() {
+    let mut lexer: Lexer = Lexer::new();
+    lexer.set_input("y = 99_999_999_999_999_999_999".to_string());
+    assert_eq!(
+      lexer.tokenize().map(|_| ()),
+      Err(
+        "Integer literal '99_999_999_999_999_999_999' at line 1, column 5 doesn't fit in an integer"
+          .to_string()
+      )
+    );
+    // Floats of any size are accepted
+    assert_eq!(
+      tokenize("99999999999999999999.5")[0].get_value(),
+      "99999999999999999999.5"
+    );
+  }
+
+  #[test]
+  fn large_input_is_lexed_in_linear_time() {
+    // Rescanning the input for each character would take minutes at this size
+    let line: &str = "total = total + 12.5 * \"é\" // comment\n";
+    let tokens: Vec<Token> = tokenize(&line.repeat(50_000));
+    // 7 tokens per line, and the EOF token
+    assert_eq!(tokens.len(), 7 * 50_000 + 1);
+    let last: &Token = &tokens[tokens.len() - 2];
+    assert_eq!(last.get_value(), "é");
+    assert_eq!((last.get_line(), last.get_column()), (50_000, 24));
+  }
+
+  #[test]
+  fn invalid_control_character_is_escaped_in_the_error() {
+    let mut lexer: Lexer = Lexer::new();
+    lexer.set_input("x = 1\u{7}".to_string());
+    assert_eq!(
+      lexer.tokenize().map(|_| ()),
+      Err("Invalid character '\\u{7}' at line 1, column 6".to_string())
+    );
+    lexer.set_input("x\u{1b}[31m".to_string());
+    assert_eq!(
+      lexer.tokenize().map(|_| ()),
+      Err("Invalid character '\\u{1b}' at line 1, column 2".to_string())
+    );
+  }
+}
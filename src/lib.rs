@@ -0,0 +1,141 @@
+//! An interpreter for a custom programming language, usable as a library.
+//!
+//! `run` interprets a whole program and returns its final value. `parse` and `evaluate` split it
+//! in two, for callers that inspect the parsed program or configure the `Context` it runs in, as
+//! the command line interpreter does.
+
+// Token and value variants are named in SCREAMING_CASE throughout the crate.
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod ast;
+mod builtins;
+pub mod context;
+pub mod diagnostics;
+pub mod lexer;
+pub mod lint;
+pub mod parser;
+pub mod token;
+pub mod visitor;
+
+pub use crate::ast::{ASTree, RuntimeValue};
+pub use crate::context::Context;
+pub use crate::diagnostics::SourceError;
+pub use crate::lexer::Lexer;
+pub use crate::parser::Parser;
+
+use std::fmt;
+
+/// Maximum number of parse errors reported at once, parsing stops at the next one.
+pub const MAX_PARSE_ERRORS: usize = 10;
+
+/// An error stopping the interpretation of a program, by the stage it occurred in. Each error
+/// carries its message and the line and column it refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpretError {
+  /// The source contains text that isn't a token.
+  LEX(SourceError),
+  /// The tokens don't form a program, with every error found before parsing stopped.
+  PARSE(Vec<SourceError>),
+  /// The program failed while being evaluated.
+  RUNTIME(SourceError),
+}
+
+impl InterpretError {
+  /// Returns the name of the stage the error occurred in, e.g. `parsing`.
+  pub fn get_stage(&self) -> &'static str {
+    match self {
+      InterpretError::LEX(_) => "lexing",
+      InterpretError::PARSE(_) => "parsing",
+      InterpretError::RUNTIME(_) => "runtime",
+    }
+  }
+
+  /// Returns the errors, a single one except for parse errors.
+  pub fn get_errors(&self) -> &[SourceError] {
+    match self {
+      InterpretError::LEX(error) | InterpretError::RUNTIME(error) => std::slice::from_ref(error),
+      InterpretError::PARSE(errors) => errors,
+    }
+  }
+}
+
+impl fmt::Display for InterpretError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let messages: Vec<&str> = self
+      .get_errors()
+      .iter()
+      .map(|error| error.get_message().as_str())
+      .collect();
+    write!(
+      f,
+      "Error during {}: {}",
+      self.get_stage(),
+      messages.join("\n")
+    )
+  }
+}
+
+impl std::error::Error for InterpretError {}
+
+/// Lexes and parses a program.
+///
+/// # Arguments
+///
+/// * `code` - The source code of the program.
+///
+/// # Returns
+///
+/// * `Ok(ASTree)` holding the root block of the program.
+/// * `Err(InterpretError)` if lexing or parsing fails.
+pub fn parse(code: &str) -> Result<ASTree, InterpretError> {
+  let mut lexer = Lexer::new();
+  lexer.set_input(code.to_string());
+  let tokens = lexer
+    .tokenize()
+    .map_err(|error| InterpretError::LEX(SourceError::new(error)))?;
+
+  let mut parser = Parser::new();
+  parser.set_tokens(tokens);
+  parser.set_max_errors(MAX_PARSE_ERRORS);
+  parser
+    .parse()
+    .map_err(|errors| InterpretError::PARSE(errors.into_iter().map(SourceError::new).collect()))
+}
+
+/// Evaluates a parsed program.
+///
+/// # Arguments
+///
+/// * `tree` - The root block of the program, as returned by `parse`.
+/// * `context` - The context to evaluate the program in, holding its settings such as limits.
+///
+/// # Returns
+///
+/// * `Ok(RuntimeValue)` holding the value of the program's last statement, or the value given to
+///   a top-level `return`.
+/// * `Err(InterpretError)` if evaluation fails.
+pub fn evaluate(tree: &ASTree, context: &mut Context) -> Result<RuntimeValue, InterpretError> {
+  let value: RuntimeValue = tree.eval(context).map_err(|error| {
+    InterpretError::RUNTIME(SourceError::with_location(
+      error,
+      context.take_error_location(),
+    ))
+  })?;
+  Ok(context.take_return().unwrap_or(value))
+}
+
+/// Interprets a program with the default settings. Nothing is printed, other than the values the
+/// program itself prints with `pprint`.
+///
+/// # Arguments
+///
+/// * `code` - The source code of the program.
+///
+/// # Returns
+///
+/// * `Ok(RuntimeValue)` holding the program's final value, see `evaluate`.
+/// * `Err(InterpretError)` if lexing, parsing or evaluation fails.
+pub fn run(code: &str) -> Result<RuntimeValue, InterpretError> {
+  let tree: ASTree = parse(code)?;
+  evaluate(&tree, &mut Context::new())
+}
@@ -0,0 +1,215 @@
+//! Static checks run on a parsed program before it is evaluated.
+//!
+//! Lints never change how a program runs, they only report code that is likely to be a mistake.
+//! The interpreter prints them as warnings, or refuses to run the program in strict mode.
+
+use crate::ast::{ASTree, RuntimeValue};
+use crate::context::Context;
+use crate::token::TokenType;
+use crate::visitor::Visitor;
+
+/// Flags `while` and `for` loops whose condition is always true and `loop` loops, when their body
+/// never exits them.
+struct InfiniteLoopLint {
+  warnings: Vec<String>,
+}
+
+impl InfiniteLoopLint {
+  /// Checks whether an expression only depends on literals, so that it always evaluates to the
+  /// same value.
+  ///
+  /// # Arguments
+  ///
+  /// * `node` - The root of the expression.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the expression is constant, `false` otherwise.
+  fn is_constant(node: &ASTree) -> bool {
+    matches!(
+      node.get_type(),
+      TokenType::NUMERIC
+        | TokenType::STRING
+        | TokenType::BOOL
+        | TokenType::BINARYOP
+        | TokenType::UNARYOP
+        | TokenType::NOT
+    ) && node
+      .get_children()
+      .iter()
+      .all(|child| Self::is_constant(child))
+  }
+
+  /// Checks whether a condition is a constant expression evaluating to true.
+  ///
+  /// # Arguments
+  ///
+  /// * `condition` - The condition to check.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the condition is always true, `false` otherwise.
+  fn is_always_true(condition: &ASTree) -> bool {
+    // Constant expressions can't touch variables, so evaluating them has no side effect
+    Self::is_constant(condition)
+      && matches!(
+        condition.eval(&mut Context::new()),
+        Ok(RuntimeValue::BOOL(true))
+      )
+  }
+
+  /// Checks whether a statement contains a way out of the loop it is in, a `throw`, a `return`,
+  /// or a `break` targeting the loop or a loop enclosing it.
+  ///
+  /// # Arguments
+  ///
+  /// * `node` - The statement to check.
+  /// * `nested` - Whether the statement is inside a loop nested in the checked one.
+  /// * `nested_labels` - The labels of the loops nested in the checked one that the statement is
+  ///   inside.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if the statement may exit the loop, `false` otherwise.
+  fn exits_loop(node: &ASTree, nested: bool, nested_labels: &[&String]) -> bool {
+    match node.get_type() {
+      TokenType::THROW | TokenType::RETURN => true,
+      // Labels are unique among enclosing loops, so a labeled break targets either a nested loop
+      // or a loop at least as far out as the checked one, exiting it
+      TokenType::BREAK => match node.get_children().first() {
+        Option::Some(target) => !nested_labels.contains(&target.get_token().get_value()),
+        Option::None => !nested,
+      },
+      // Loops can't be exited from within a function definition or a lambda
+      TokenType::FN | TokenType::LAMBDA => false,
+      // Only the body of a nested loop is inside it, a break in its else block exits this loop
+      TokenType::WHILE | TokenType::FOR | TokenType::LOOP | TokenType::REPEAT => {
+        let mut body_labels: Vec<&String> = nested_labels.to_vec();
+        body_labels.extend(node.get_loop_label());
+        node.get_children().iter().any(|child| {
+          if std::ptr::eq(&**child, node.get_loop_body()) {
+            Self::exits_loop(child, true, &body_labels)
+          } else {
+            Self::exits_loop(child, nested, nested_labels)
+          }
+        })
+      }
+      _ => node
+        .get_children()
+        .iter()
+        .any(|child| Self::exits_loop(child, nested, nested_labels)),
+    }
+  }
+
+  /// Records a warning if a loop's condition is always true and its body never exits it.
+  ///
+  /// # Arguments
+  ///
+  /// * `node` - The `while` or `for` loop.
+  fn check_loop(&mut self, node: &ASTree) {
+    if Self::is_always_true(node.get_loop_condition())
+      && !Self::exits_loop(node.get_loop_body(), false, &[])
+    {
+      self.warnings.push(format!(
+        "{} loop at {} may never terminate, its condition is always true",
+        if matches!(node.get_type(), TokenType::FOR) {
+          "For"
+        } else {
+          "While"
+        },
+        node.get_token().get_location()
+      ));
+    }
+  }
+
+  /// Records a warning if a `loop` loop's body never exits it, since nothing else ends it.
+  ///
+  /// # Arguments
+  ///
+  /// * `node` - The `loop` loop.
+  fn check_unconditional_loop(&mut self, node: &ASTree) {
+    if !Self::exits_loop(node.get_loop_body(), false, &[]) {
+      self.warnings.push(format!(
+        "Loop at {} never terminates, its body has no break, return or throw exiting it",
+        node.get_token().get_location()
+      ));
+    }
+  }
+}
+
+impl Visitor for InfiniteLoopLint {
+  fn visit_while(&mut self, node: &ASTree) {
+    self.check_loop(node);
+  }
+
+  fn visit_for(&mut self, node: &ASTree) {
+    self.check_loop(node);
+  }
+
+  fn visit_loop(&mut self, node: &ASTree) {
+    self.check_unconditional_loop(node);
+  }
+}
+
+/// Runs every lint on a parsed program.
+///
+/// # Arguments
+///
+/// * `tree` - The root of the parsed program.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The warnings found, in the order they appear in the program.
+pub fn lint(tree: &ASTree) -> Vec<String> {
+  let mut infinite_loops = InfiniteLoopLint {
+    warnings: Vec::new(),
+  };
+  tree.accept(&mut infinite_loops);
+  infinite_loops.warnings
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Parses a program that is expected to be valid and lints it.
+  fn lint_code(code: &str) -> Vec<String> {
+    lint(&crate::parse(code).expect("The program should parse"))
+  }
+
+  #[test]
+  fn always_true_loop_without_exit_is_flagged() {
+    assert_eq!(
+      lint_code("x = 0\nwhile (true) { x = x + 1 }"),
+      ["While loop at line 2, column 1 may never terminate, its condition is always true"]
+    );
+    assert_eq!(lint_code("for (i = 0; 1 == 1; i = i + 1) {}").len(), 1);
+    assert_eq!(lint_code("loop { x = 1 }").len(), 1);
+  }
+
+  #[test]
+  fn always_true_loop_with_exit_is_not_flagged() {
+    assert!(lint_code("while (true) { break }").is_empty());
+    assert!(lint_code("fn f() { while (true) { return 1 } }").is_empty());
+    assert!(lint_code("while (true) { throw \"stop\" }").is_empty());
+    assert!(lint_code("loop { if (true) { break } }").is_empty());
+  }
+
+  #[test]
+  fn loop_with_a_variable_condition_is_not_flagged() {
+    assert!(lint_code("x = 0\nwhile (x < 3) { x = x + 1 }").is_empty());
+  }
+
+  #[test]
+  fn break_of_a_nested_loop_does_not_exit_the_outer_one() {
+    assert_eq!(
+      lint_code("while (true) { while (true) { break } }"),
+      ["While loop at line 1, column 1 may never terminate, its condition is always true"]
+    );
+    assert!(lint_code("outer: while (true) { while (true) { break outer } }").is_empty());
+    assert_eq!(
+      lint_code("while (true) { inner: while (true) { break inner } }").len(),
+      1
+    );
+  }
+}
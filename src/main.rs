@@ -1,59 +1,438 @@
 //! An interpreter for a custom programming language.
 //!
-//! This module serves as the entry point for the interpreter, handling
-//! reading input files, lexing, parsing, and evaluating the code.
-
-mod ast;
-mod context;
-mod lexer;
-mod parser;
-mod token;
-
-use crate::context::Context;
-use crate::lexer::Lexer;
-use crate::parser::Parser;
+//! This module serves as the entry point for the interpreter, handling reading input files,
+//! command line options and the interactive REPL. Lexing, parsing and evaluating the code is done
+//! by the library.
+
+use interpreter::InterpretError;
+use interpreter::SourceError;
+use interpreter::context::{Context, DEFAULT_MAX_CALL_DEPTH};
+use interpreter::lint;
+use interpreter::{Lexer, MAX_PARSE_ERRORS, Parser, RuntimeValue};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
+use std::io;
+use std::io::{BufRead, Read, Write};
+use std::process;
+use std::time::Duration;
+
+/// Printed by `--help`.
+const USAGE: &str = "Usage: interpreter [--trace] [--strict] [--profile] [--json-ast] [--ast-dot] \
+[--dump-scopes-on-error] [--max-steps N] [--timeout MS] [--max-call-depth N] [--prompt TEXT] \
+[file | -]\nWithout a file, starts an interactive session.";
+
+/// The prompt of the REPL when `--prompt` isn't given.
+const DEFAULT_PROMPT: &str = ">>> ";
+
+/// The prompt of the REPL while the input so far is an incomplete statement.
+const CONTINUATION_PROMPT: &str = "... ";
+
+/// Command line options accepted by the interpreter.
+struct Options {
+  /// The path of the file to interpret, "-" for stdin, or `None` to start a REPL.
+  path: Option<String>,
+  /// Whether every evaluated node should be traced.
+  trace: bool,
+  /// Whether lint warnings should be treated as errors.
+  strict: bool,
+  /// Whether the number of evaluations per line should be reported.
+  profile: bool,
+  /// Whether the parse tree should be printed as JSON instead of being evaluated.
+  json_ast: bool,
+  /// Whether the parse tree should be printed as a Graphviz DOT graph instead of being evaluated.
+  ast_dot: bool,
+  /// Whether the scopes should be printed when a runtime error occurs.
+  dump_scopes_on_error: bool,
+  /// The maximum number of evaluation steps.
+  max_steps: Option<u64>,
+  /// The maximum evaluation duration.
+  timeout: Option<Duration>,
+  /// The maximum number of nested function calls.
+  max_call_depth: usize,
+  /// The prompt of the REPL.
+  prompt: String,
+}
+
+/// Parses the numeric value following a command line option.
+///
+/// # Arguments
+///
+/// * `option` - The name of the option, used in error messages.
+/// * `value` - The value following the option, if any.
+///
+/// # Returns
+///
+/// * `Ok(u64)` holding the parsed value.
+/// * `Err(String)` if the value is missing or isn't a non-negative integer.
+fn parse_option_value(option: &str, value: Option<&String>) -> Result<u64, String> {
+  match value {
+    Option::Some(value) => value
+      .parse::<u64>()
+      .map_err(|_| format!("Invalid value for {option}: {value}")),
+    Option::None => Err(format!("Missing value for {option}")),
+  }
+}
+
+/// Parses the command line arguments into `Options`.
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments, excluding the program name.
+///
+/// # Returns
+///
+/// * `Ok(Options)` holding the parsed options.
+/// * `Err(String)` if an option is unknown or has an invalid value, or several files are given.
+fn parse_args(args: &[String]) -> Result<Options, String> {
+  let mut paths: Vec<&String> = Vec::new();
+  let mut trace: bool = false;
+  let mut strict: bool = false;
+  let mut profile: bool = false;
+  let mut json_ast: bool = false;
+  let mut ast_dot: bool = false;
+  let mut dump_scopes_on_error: bool = false;
+  let mut max_steps: Option<u64> = Option::None;
+  let mut timeout: Option<Duration> = Option::None;
+  let mut max_call_depth: usize = DEFAULT_MAX_CALL_DEPTH;
+  let mut prompt: String = DEFAULT_PROMPT.to_string();
+
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--trace" => trace = true,
+      "--strict" => strict = true,
+      "--profile" => profile = true,
+      "--json-ast" => json_ast = true,
+      "--ast-dot" => ast_dot = true,
+      "--dump-scopes-on-error" => dump_scopes_on_error = true,
+      "--max-steps" => max_steps = Some(parse_option_value(arg, args.next())?),
+      "--timeout" => timeout = Some(Duration::from_millis(parse_option_value(arg, args.next())?)),
+      "--max-call-depth" => max_call_depth = parse_option_value(arg, args.next())? as usize,
+      "--prompt" => match args.next() {
+        Option::Some(value) => prompt = value.clone(),
+        Option::None => return Err(format!("Missing value for {arg}")),
+      },
+      _ if arg.starts_with("--") => return Err(format!("Unknown option: {arg}")),
+      _ => paths.push(arg),
+    }
+  }
+
+  if paths.len() > 1 {
+    return Err(format!(
+      "Expected at most one input file, found {}",
+      paths.len()
+    ));
+  }
+
+  Ok(Options {
+    path: paths.first().map(|path| path.to_string()),
+    trace,
+    strict,
+    profile,
+    json_ast,
+    ast_dot,
+    dump_scopes_on_error,
+    max_steps,
+    timeout,
+    max_call_depth,
+    prompt,
+  })
+}
+
+/// Formats an error of the interpreter as diagnostics, one line per error, located in the source.
+///
+/// # Arguments
+///
+/// * `error` - The error stopping the interpretation.
+/// * `options` - The command line options, naming the interpreted file.
+///
+/// # Returns
+///
+/// * `String` - The diagnostics, without a trailing newline.
+fn format_error(error: &InterpretError, options: &Options) -> String {
+  error
+    .get_errors()
+    .iter()
+    .map(|source_error| source_error.format(source_name(options), error.get_stage()))
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+/// Names the interpreted source in diagnostics.
+///
+/// # Arguments
+///
+/// * `options` - The command line options.
+///
+/// # Returns
+///
+/// * `&str` - The path of the interpreted file, `<stdin>`, or `<repl>`.
+fn source_name(options: &Options) -> &str {
+  match options.path.as_deref() {
+    Option::Some("-") => "<stdin>",
+    Option::Some(path) => path,
+    Option::None => "<repl>",
+  }
+}
+
+/// Creates the context programs are evaluated in, with the settings of the command line options
+/// except for the timeout, which starts when it is set.
+///
+/// # Arguments
+///
+/// * `options` - The command line options.
+///
+/// # Returns
+///
+/// * `Context` - The configured context.
+fn new_context(options: &Options) -> Context {
+  let mut context = Context::new();
+  if options.trace {
+    context.set_trace(Some(2));
+  }
+  context.set_max_steps(options.max_steps);
+  context.set_max_call_depth(options.max_call_depth);
+  context.set_profiling(options.profile);
+  context.set_dump_scopes_on_error(options.dump_scopes_on_error);
+  context
+}
 
 /// Interprets the given code string by lexing, parsing, and evaluating it.
 ///
 /// # Arguments
 ///
 /// * `code` - The code string to be interpreted.
-fn interpret(code: String) {
-  let mut lexer = Lexer::new();
-  let mut parser = Parser::new();
-  let mut context = Context::new();
+/// * `options` - The command line options.
+///
+/// # Returns
+///
+/// * `Ok(())` if the program ran to completion.
+/// * `Err(String)` holding the diagnostics of the error stopping it, one line per error.
+fn interpret(code: String, options: &Options) -> Result<(), String> {
+  let mut context = new_context(options);
 
-  lexer.set_input(code);
-  let tokens = match lexer.tokenize() {
-    Err(error) => panic!("Error during lexing: {:?}", error),
-    Ok(toks) => toks,
-  };
+  let tree = interpreter::parse(&code).map_err(|error| format_error(&error, options))?;
+
+  if options.json_ast {
+    println!("{}", tree.to_json());
+    return Ok(());
+  }
+  if options.ast_dot {
+    print!("{}", tree.to_dot());
+    return Ok(());
+  }
+
+  for warning in lint::lint(&tree) {
+    if options.strict {
+      return Err(SourceError::new(warning).format(source_name(options), "linting"));
+    }
+    eprintln!("Warning: {warning}");
+  }
+
+  // Includes are resolved relative to the directory of the including file
+  if let Option::Some(path) = options.path.as_deref()
+    && path != "-"
+    && let Ok(path) = fs::canonicalize(path)
+  {
+    context
+      .enter_source_file(path)
+      .expect("The context has no source file yet");
+  }
+
+  // Started last so that only evaluation counts towards the timeout
+  context.set_timeout(options.timeout);
+  let result = interpreter::evaluate(&tree, &mut context);
+  if let Some(profile) = context.get_profile() {
+    print_profile(&code, profile);
+  }
+  result.map(|_return_value| ()).map_err(|error| {
+    if let Some(scopes) = context.take_error_scopes() {
+      eprint!("Scopes where the error occurred:\n{scopes}");
+    }
+    format_error(&error, options)
+  })
+}
 
+/// Lexes, parses and evaluates an input of the REPL in its context.
+///
+/// # Arguments
+///
+/// * `input` - The lines read since the last complete input.
+/// * `context` - The context kept for the whole session.
+/// * `options` - The command line options.
+///
+/// # Returns
+///
+/// * `Ok(Some(RuntimeValue))` holding the value of the input, see `ASTree::eval_line`.
+/// * `Ok(None)` if the input ends in the middle of a statement, so more lines must be read.
+/// * `Err(String)` holding the diagnostics of the error stopping the input.
+fn eval_repl_input(
+  input: &str,
+  context: &mut Context,
+  options: &Options,
+) -> Result<Option<RuntimeValue>, String> {
+  let mut lexer = Lexer::new();
+  lexer.set_input(input.to_string());
+  let tokens = lexer
+    .tokenize()
+    .map_err(|error| format_error(&InterpretError::LEX(SourceError::new(error)), options))?;
+
+  let mut parser = Parser::new();
   parser.set_tokens(tokens);
+  parser.set_max_errors(MAX_PARSE_ERRORS);
   let tree = match parser.parse() {
-    Err(error) => panic!("Error during parsing: {error}"),
     Ok(tree) => tree,
+    Err(_) if parser.is_incomplete() => return Ok(Option::None),
+    Err(errors) => {
+      let error = InterpretError::PARSE(errors.into_iter().map(SourceError::new).collect());
+      return Err(format_error(&error, options));
+    }
   };
 
-  match tree.eval(&mut context) {
-    Ok(_return_value) => {}
-    Err(error) => panic!("Error during runtime: {error}"),
+  tree.eval_line(context).map(Option::Some).map_err(|error| {
+    let location: Option<(usize, usize)> = context.take_error_location();
+    format_error(
+      &InterpretError::RUNTIME(SourceError::with_location(error, location)),
+      options,
+    )
+  })
+}
+
+/// Runs an interactive session, evaluating the statements read from stdin in a context kept
+/// between inputs and printing their values. An input spans several lines while it ends in the
+/// middle of a statement, e.g. after `fn f() {`. Errors are reported without ending the session,
+/// which ends with the input.
+///
+/// # Arguments
+///
+/// * `options` - The command line options.
+fn repl(options: &Options) {
+  let mut context = new_context(options);
+  context.push_named_scope("global_block".to_string());
+  let mut input: String = String::new();
+  loop {
+    let prompt: &str = if input.is_empty() {
+      &options.prompt
+    } else {
+      CONTINUATION_PROMPT
+    };
+    print!("{prompt}");
+    // The prompt has no newline, so it is only shown once flushed
+    let _ = io::stdout().flush();
+
+    let mut line: String = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+      // End of input, e.g. Ctrl-D, ends the line the prompt is on
+      Ok(0) => {
+        println!();
+        return;
+      }
+      Ok(_) => input.push_str(&line),
+      Err(error) => {
+        eprintln!("Error: Failed to read stdin: {error}");
+        process::exit(1);
+      }
+    }
+
+    match eval_repl_input(&input, &mut context, options) {
+      Ok(Option::None) => continue,
+      Ok(Option::Some(RuntimeValue::NULL)) => {}
+      Ok(Option::Some(value)) => println!("{value}"),
+      Err(diagnostics) => eprintln!("{diagnostics}"),
+    }
+    input.clear();
+  }
+}
+
+/// Reads the program's source, from a file or from stdin, and decodes it as UTF-8.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to read, or `"-"` to read from stdin.
+///
+/// # Returns
+///
+/// * `Ok(String)` holding the source code.
+/// * `Err(String)` if it can't be read or isn't valid UTF-8, with the offset of the first invalid
+///   byte.
+fn read_source(path: &str) -> Result<String, String> {
+  let mut bytes: Vec<u8> = Vec::new();
+  // Following Unix convention, "-" reads the program from stdin
+  let (name, read) = if path == "-" {
+    ("stdin", io::stdin().read_to_end(&mut bytes).map(|_| ()))
+  } else {
+    (path, fs::read(path).map(|content| bytes = content))
   };
+  read.map_err(|error| format!("Failed to read {name}: {error}"))?;
+  String::from_utf8(bytes).map_err(|error| {
+    format!(
+      "{name} is not valid UTF-8 at byte {}",
+      error.utf8_error().valid_up_to()
+    )
+  })
+}
+
+/// Prints the number of evaluated nodes of each source line to stderr, in line order.
+///
+/// # Arguments
+///
+/// * `code` - The interpreted code string.
+/// * `profile` - The number of evaluated nodes per source line.
+fn print_profile(code: &str, profile: &HashMap<usize, u64>) {
+  let per_line: BTreeMap<&usize, &u64> = profile.iter().collect();
+
+  let lines: Vec<&str> = code.lines().collect();
+  eprintln!("Profile, evaluations per line:");
+  for (line, count) in per_line {
+    eprintln!(
+      "{:>5} | {:>10} | {}",
+      line,
+      count,
+      lines.get(*line - 1).unwrap_or(&"").trim()
+    );
+  }
 }
 
 fn main() {
   let argv: Vec<String> = env::args().collect();
-  let argc: usize = argv.len();
-
-  if argc != 2 {
-    panic!("Expected two arguments, found {argc}");
+  // These only print information, so they are handled before looking for an input file
+  if argv[1..].iter().any(|arg| arg == "--help") {
+    println!("{USAGE}");
+    return;
   }
+  if argv[1..].iter().any(|arg| arg == "--version") {
+    println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    return;
+  }
+  let options: Options = match parse_args(&argv[1..]) {
+    Ok(options) => options,
+    Err(error) => {
+      eprintln!("Error: {error}, see --help for usage");
+      process::exit(1);
+    }
+  };
+  let Option::Some(path) = options.path.as_deref() else {
+    repl(&options);
+    return;
+  };
 
-  let file_content: String =
-    fs::read_to_string(argv[1].clone()).expect("Failed to read file: {argv[1]}");
-  print!("{file_content}");
+  // Reported without a panic, since there is no interpreter bug or program position to show
+  let file_content: String = match read_source(path) {
+    Ok(content) => content,
+    Err(error) => {
+      eprintln!("Error: {error}");
+      process::exit(1);
+    }
+  };
+  // The JSON and DOT outputs are meant for tools, so they are the only thing printed
+  if !options.json_ast && !options.ast_dot {
+    print!("{file_content}");
+  }
 
-  interpret(file_content);
+  // Errors in the program are reported as diagnostics, panics are left to interpreter bugs
+  if let Err(diagnostics) = interpret(file_content, &options) {
+    eprintln!("{diagnostics}");
+    process::exit(1);
+  }
 }
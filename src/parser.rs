@@ -11,6 +11,8 @@ use crate::token::TokenType;
 enum ShuntingType {
   /// Represents an operator with its priority.
   OPERATOR(u8),
+  /// Represents a prefix unary operator.
+  PREFIX,
   /// Represents an operand.
   OPERAND,
   /// Represents the end of an expression.
@@ -18,11 +20,24 @@ enum ShuntingType {
 }
 
 /// Parser struct for parsing tokens into an Abstract Syntax Tree (AST).
+///
+/// Cloning a parser snapshots its position, so a caller can attempt a parse on the clone and fall
+/// back to the original if it fails.
+#[derive(Clone)]
 pub struct Parser {
   /// The list of tokens to be parsed.
   tokens: Vec<Token>,
   /// The current position in the token list.
   pos: usize,
+  /// The labels of the loops enclosing the statement being parsed, innermost last, `None` for an
+  /// unlabeled loop.
+  loop_labels: Vec<Option<String>>,
+  /// The errors recovered from so far, parsing resumed at the next statement after each of them.
+  errors: Vec<String>,
+  /// The maximum number of errors reported by `parse`, parsing stops at the first error when 1.
+  max_errors: usize,
+  /// Whether the last call to `parse` failed only because the tokens ended too early.
+  incomplete: bool,
 }
 
 impl Parser {
@@ -31,6 +46,10 @@ impl Parser {
     Parser {
       tokens: Vec::new(),
       pos: 0,
+      loop_labels: Vec::new(),
+      errors: Vec::new(),
+      max_errors: 1,
+      incomplete: false,
     }
   }
 
@@ -43,14 +62,21 @@ impl Parser {
     &self.tokens[self.pos]
   }
 
-  /// Peeks at the next token without advancing the position.
+  /// Peeks at the token `n` positions after the current one without advancing the position.
+  /// Peeking past the end of the tokens returns the final EOF token.
+  ///
+  /// # Arguments
+  ///
+  /// * `n` - The number of tokens to look ahead, 0 being the current token.
   ///
   /// # Returns
   ///
-  /// * Option::None if there is no next token
-  /// * Option::Some(&Token) if there is a next token
-  fn peek_next(&self) -> Option<&Token> {
-    self.tokens.get(self.pos + 1)
+  /// * `&Token` - A reference to the token `n` positions ahead, or to the EOF token.
+  fn peek_ahead(&self, n: usize) -> &Token {
+    match self.tokens.get(self.pos + n) {
+      Option::Some(token) => token,
+      Option::None => self.tokens.last().unwrap(),
+    }
   }
 
   /// Advances the position and returns the current token.
@@ -77,9 +103,9 @@ impl Parser {
   fn consume(&mut self, token_type: TokenType) -> Result<Token, String> {
     if *self.peek().get_type() != token_type {
       return Err(format!(
-        "Expected {:?} before position {}, found {:?}",
+        "Expected {:?} before {}, found {:?}",
         token_type,
-        self.peek().get_position(),
+        self.peek().get_location(),
         self.peek().get_type()
       ));
     }
@@ -95,7 +121,36 @@ impl Parser {
     self.tokens = tokens;
   }
 
-  /// Matches an operator string to its corresponding priority.
+  /// Sets the maximum number of errors reported by `parse`. Above 1, the parser recovers from an
+  /// error by skipping to the next statement, until that many errors were found. The cap avoids
+  /// reporting a cascade of errors caused by a single mistake.
+  ///
+  /// # Arguments
+  ///
+  /// * `max_errors` - The maximum number of errors to report, at least 1.
+  pub fn set_max_errors(&mut self, max_errors: usize) {
+    self.max_errors = max_errors.max(1);
+  }
+
+  /// Checks whether the last call to `parse` failed only because the tokens ended before a
+  /// statement was complete, e.g. after `fn f() {` or `x = [1,`. Such input isn't erroneous yet,
+  /// a REPL can read more lines and parse again instead of reporting the error.
+  ///
+  /// # Returns
+  ///
+  /// * `true` if parsing failed with a single error, at the end of the tokens, `false` if it
+  ///   succeeded or the input has an error before its end.
+  pub fn is_incomplete(&self) -> bool {
+    self.incomplete
+  }
+
+  /// Matches an operator string to its corresponding priority, higher priorities bind tighter.
+  ///
+  /// From lowest to highest: logical or, logical and, the `not` prefix operator, equality,
+  /// comparison, additive, multiplicative, then the `!` prefix operator. `not` binds looser than
+  /// comparisons, so `not a == b` is `not (a == b)`, but tighter than logical operators, so
+  /// `not a && b` is `(not a) && b`. `!` binds tighter than every binary operator, so `!a == b`
+  /// is `(!a) == b`. Unary minus shares its value with subtraction, see `token_priority`.
   ///
   /// # Arguments
   ///
@@ -106,18 +161,35 @@ impl Parser {
   /// * `u8` - The priority of the operator.
   fn match_operator_to_priority(operator: &str) -> u8 {
     match operator {
-      "==" => 1,
-      "!=" => 1,
-      "&&" => 1,
       "||" => 1,
-      "+" => 2,
-      "-" => 2,
-      "/" => 3,
-      "*" => 3,
+      "&&" => 2,
+      "not" => 3,
+      "==" | "!=" => 4,
+      "<" | "<=" | ">" | ">=" | "in" => 5,
+      "+" | "-" => 6,
+      "*" | "/" | "%" => 7,
+      "!" => 8,
       _ => panic!("Unsupported Operator in match_operator_to_priority"),
     }
   }
 
+  /// Gets the priority of an operator token, like `match_operator_to_priority` except that unary
+  /// minus binds tighter than every binary operator, so `-a * b` is `(-a) * b`.
+  ///
+  /// # Arguments
+  ///
+  /// * `token` - The operator token.
+  ///
+  /// # Returns
+  ///
+  /// * `u8` - The priority of the operator.
+  fn token_priority(token: &Token) -> u8 {
+    match token.get_type() {
+      TokenType::UNARYOP => 8,
+      _ => Self::match_operator_to_priority(token.get_value().as_str()),
+    }
+  }
+
   /// Converts a token to its corresponding ShuntingType.
   ///
   /// # Arguments
@@ -132,9 +204,14 @@ impl Parser {
       TokenType::NUMERIC => ShuntingType::OPERAND,
       TokenType::IDENTIFIER => ShuntingType::OPERAND,
       TokenType::STRING => ShuntingType::OPERAND,
+      TokenType::BOOL => ShuntingType::OPERAND,
+      TokenType::LBRACKET => ShuntingType::OPERAND,
+      // A parenthesized expression is parsed as a whole, like an array
+      TokenType::LPAREN => ShuntingType::OPERAND,
       TokenType::BINARYOP => {
         ShuntingType::OPERATOR(Self::match_operator_to_priority(token.get_value().as_str()))
       }
+      TokenType::NOT => ShuntingType::PREFIX,
       _ => ShuntingType::END,
     }
   }
@@ -145,7 +222,7 @@ impl Parser {
   /// # Returns
   ///
   /// * `Result<Vec<ASTree>, String>` - A result containing the postfix ASTree vector or an error
-  /// message.
+  ///   message.
   fn shunting_yard(&mut self) -> Result<Vec<ASTree>, String> {
     let mut output: Vec<ASTree> = Vec::new();
     let mut operator_stack: Vec<Token> = Vec::new();
@@ -154,23 +231,33 @@ impl Parser {
 
     // Loop can't be infinite, worst case will break when encountering a TokenType::EOF (ShuntingType::END)
     loop {
-      match Self::convert_to_shunting_type(self.peek()) {
+      // A lambda is an operand, even when its parameter list starts with a parenthesis
+      let shunting_type: ShuntingType = if self.is_lambda_start() {
+        ShuntingType::OPERAND
+      } else if matches!(prev, ShuntingType::OPERATOR(_))
+        && matches!(self.peek().get_type(), TokenType::BINARYOP)
+        && self.peek().get_value() == "-"
+      {
+        // A '-' where an operand is expected negates it rather than subtracting
+        ShuntingType::PREFIX
+      } else {
+        Self::convert_to_shunting_type(self.peek())
+      };
+      match shunting_type {
         ShuntingType::OPERATOR(val) => {
           if matches!(prev, ShuntingType::OPERATOR(_)) {
             return Err(format!(
-              "Invalid operator placement at position {}",
-              self.peek().get_position()
+              "Invalid operator placement at {}",
+              self.peek().get_location()
             ));
           }
 
           // auto-formatting makes this hard to read
           // while there are operators on the stack with greater or equal precedence than the
-          // current operator, pop them to the output
-          while operator_stack.len() > 0
-            && val
-              <= Self::match_operator_to_priority(
-                operator_stack.last().unwrap().get_value().as_str(),
-              )
+          // current operator, pop them to the output, popping on equality makes every binary
+          // operator left-associative
+          while !operator_stack.is_empty()
+            && val <= Self::token_priority(operator_stack.last().unwrap())
           {
             output.push(ASTree::new(operator_stack.pop().unwrap()))
           }
@@ -178,32 +265,96 @@ impl Parser {
           operator_stack.push(self.advance());
           prev = ShuntingType::OPERATOR(val);
         }
+        ShuntingType::PREFIX => {
+          // A prefix operator after an operand starts a different expression
+          if matches!(prev, ShuntingType::OPERAND) {
+            break;
+          }
+
+          // Prefix operators apply to what follows them, so nothing is popped. They stay on the
+          // stack until a binary operator binding looser than them pops them, or the expression
+          // ends
+          let mut operator: Token = self.advance();
+          if matches!(operator.get_type(), TokenType::BINARYOP) {
+            operator = Token::at(TokenType::UNARYOP, operator.get_value().clone(), &operator);
+          }
+          prev = ShuntingType::OPERATOR(Self::token_priority(&operator));
+          operator_stack.push(operator);
+        }
         ShuntingType::OPERAND => {
           // If the previous token was also an operand, this is a different expression
           if matches!(prev, ShuntingType::OPERAND) {
             break;
           }
 
+          // A lambda's body extends as far as possible, so nothing can follow it
+          if self.is_lambda_start() {
+            output.push(self.parse_lambda()?);
+            prev = ShuntingType::OPERAND;
+            continue;
+          }
+
+          // The value of an assignment expression extends as far as possible, which makes `:=`
+          // bind looser than any operator and right-associative
+          if matches!(self.peek_ahead(1).get_type(), TokenType::WALRUS) {
+            output.push(self.parse_walrus()?);
+            prev = ShuntingType::OPERAND;
+            continue;
+          }
+
           // If the next token is a left parenthesis, this operand is a function call
-          if self.peek_next().is_some()
-            && matches!(self.peek_next().unwrap().get_type(), TokenType::LPAREN)
-          {
-            output.push(self.parse_fn_call()?);
+          let operand: ASTree = if matches!(self.peek().get_type(), TokenType::LBRACKET) {
+            self.parse_array()?
+          } else if matches!(self.peek().get_type(), TokenType::LPAREN) {
+            self.parse_group()?
+          } else if matches!(self.peek_ahead(1).get_type(), TokenType::LPAREN) {
+            self.parse_fn_call()?
           } else {
-            output.push(ASTree::new(self.advance()));
-          }
+            ASTree::new(self.advance())
+          };
+          output.push(self.parse_postfix(operand)?);
           prev = ShuntingType::OPERAND;
         }
         ShuntingType::END => break,
       }
     }
 
+    // An expression can't end while expecting an operand, the operator pushed last is the one
+    // missing it
+    if matches!(prev, ShuntingType::OPERATOR(_))
+      && let Some(last) = operator_stack.last()
+    {
+      return Err(self.missing_operand_error(last));
+    }
+
     while !operator_stack.is_empty() {
       output.push(ASTree::new(operator_stack.pop().unwrap()));
     }
     Ok(output)
   }
 
+  /// Formats the error for an operator lacking one of its operands, e.g. `1 +`.
+  /// When the tokens ran out the message says so, the input may continue on a following line.
+  ///
+  /// # Arguments
+  ///
+  /// * `operator` - The token of the operator missing an operand.
+  ///
+  /// # Returns
+  ///
+  /// * `String` - The error message.
+  fn missing_operand_error(&self, operator: &Token) -> String {
+    let message: String = format!(
+      "Operator '{}' at {} is missing an operand",
+      operator.get_value(),
+      operator.get_location()
+    );
+    if matches!(self.peek().get_type(), TokenType::EOF) {
+      return format!("{}, found EOF", message);
+    }
+    message
+  }
+
   /// Parses an expression using the Shunting Yard algorithm and constructs the AST.
   ///
   /// # Returns
@@ -214,31 +365,44 @@ impl Parser {
     let mut output: Vec<ASTree> = Vec::new();
 
     for tree in postfix_expression {
-      if matches!(tree.get_type(), TokenType::BINARYOP) {
-        let right: ASTree = output.pop().expect("Insufficient operands for operator");
-        let left: ASTree = output.pop().expect("Insufficient operands for operator");
+      // Operands are complete trees, an operator node already having children is the inner
+      // expression of a parenthesized group
+      if !tree.get_children().is_empty() {
+        output.push(tree);
+      } else if matches!(tree.get_type(), TokenType::BINARYOP) {
+        let (left, right): (ASTree, ASTree) = match (output.pop(), output.pop()) {
+          (Option::Some(right), Option::Some(left)) => (left, right),
+          _ => return Err(self.missing_operand_error(tree.get_token())),
+        };
 
         let mut operator_node: ASTree = tree;
         operator_node.append(left);
         operator_node.append(right);
         output.push(operator_node);
+      } else if matches!(tree.get_type(), TokenType::NOT | TokenType::UNARYOP) {
+        let operand: ASTree = match output.pop() {
+          Option::Some(operand) => operand,
+          Option::None => return Err(self.missing_operand_error(tree.get_token())),
+        };
+
+        let mut operator_node: ASTree = tree;
+        operator_node.append(operand);
+        output.push(operator_node);
       } else {
         output.push(tree);
       }
     }
 
-    if output.len() == 0 {
+    if output.is_empty() {
       return Err(format!(
-        "Expected expression, found none at position {}",
-        self.peek().get_position()
+        "Expected expression, found none at {}",
+        self.peek().get_location()
       ));
     }
     if output.len() == 1 {
       return Ok(output.pop().unwrap());
     }
-    Err(format!(
-      "Expression parsing failed to resolve to singular ASTree"
-    ))
+    Err("Expression parsing failed to resolve to singular ASTree".to_string())
   }
 
   /// Parses an assignment statement.
@@ -248,14 +412,153 @@ impl Parser {
   /// * `Result<ASTree, String>` - A result containing the ASTree for the assignment
   fn parse_assign(&mut self) -> Result<ASTree, String> {
     let identifier: ASTree = ASTree::new(self.consume(TokenType::IDENTIFIER)?);
+    self.parse_assign_value(identifier)
+  }
+
+  /// Parses a destructuring assignment, e.g. `q, r = divmod(7, 2)`, binding each variable to the
+  /// element of an array at the same index.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the assignment, whose target
+  ///   is an array node holding the assigned identifiers.
+  fn parse_destructuring_assign(&mut self) -> Result<ASTree, String> {
+    let mut targets: ASTree = ASTree::new(Token::at(
+      TokenType::ARRAY,
+      "targets".to_string(),
+      self.peek(),
+    ));
+    loop {
+      let identifier: Token = self.consume(TokenType::IDENTIFIER)?;
+      if targets
+        .get_children()
+        .iter()
+        .any(|target| target.get_token().get_value() == identifier.get_value())
+      {
+        return Err(format!(
+          "Variable '{}' is assigned twice in the destructuring assignment at {}",
+          identifier.get_value(),
+          identifier.get_location()
+        ));
+      }
+      targets.append(ASTree::new(identifier));
+      if !matches!(self.peek().get_type(), TokenType::COMMA) {
+        break;
+      }
+      self.consume(TokenType::COMMA)?;
+    }
+    self.parse_assign_value(targets)
+  }
+
+  /// Parses the `= value` part of an assignment to an already parsed target.
+  ///
+  /// # Arguments
+  ///
+  /// * `target` - The ASTree being assigned to, an identifier, an index access, or the array of
+  ///   identifiers of a destructuring assignment.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the assignment
+  fn parse_assign_value(&mut self, target: ASTree) -> Result<ASTree, String> {
     let mut output: ASTree = ASTree::new(self.consume(TokenType::ASSIGN)?);
     let value: ASTree = self.parse_statement()?;
 
+    output.append(target);
+    output.append(value);
+    Ok(output)
+  }
+
+  /// Parses an assignment expression, e.g. `x := 5`, which evaluates to the assigned value.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the assignment expression,
+  ///   with the identifier and the value as its children.
+  fn parse_walrus(&mut self) -> Result<ASTree, String> {
+    let identifier: ASTree = ASTree::new(self.consume(TokenType::IDENTIFIER)?);
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::WALRUS)?);
+    output.append(identifier);
+    output.append(self.parse_expression()?);
+    Ok(output)
+  }
+
+  /// Parses a keyword-prefixed assignment, either to the global scope, e.g. `global x = 5`, or
+  /// of a constant, e.g. `const x = 5`.
+  ///
+  /// # Arguments
+  ///
+  /// * `keyword` - The type of the keyword introducing the assignment.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the assignment
+  fn parse_keyword_assign(&mut self, keyword: TokenType) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(keyword)?);
+    let identifier: ASTree = ASTree::new(self.consume(TokenType::IDENTIFIER)?);
+    self.consume(TokenType::ASSIGN)?;
+    let value: ASTree = self.parse_statement()?;
+
     output.append(identifier);
     output.append(value);
     Ok(output)
   }
 
+  /// Parses a throw statement, e.g. `throw "invalid input"`.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the throw statement, with the
+  ///   thrown expression as its child, or an error message.
+  fn parse_throw(&mut self) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::THROW)?);
+    output.append(self.parse_expression()?);
+    Ok(output)
+  }
+
+  /// Parses a return statement, e.g. `return x + 1`, or `return` alone at the end of a block to
+  /// return NULL.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the return statement, with the
+  ///   returned expression as its child if any, or an error message.
+  fn parse_return(&mut self) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::RETURN)?);
+    if !matches!(self.peek().get_type(), TokenType::RBRACE | TokenType::EOF) {
+      output.append(self.parse_expression()?);
+    }
+    Ok(output)
+  }
+
+  /// Parses an include statement, e.g. `include "lib.txt"`. The path must be a string literal, so
+  /// that the included file is known without evaluating anything.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the include statement, its
+  ///   child is the path.
+  fn parse_include(&mut self) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::INCLUDE)?);
+    output.append(ASTree::new(self.consume(TokenType::STRING)?));
+    Ok(output)
+  }
+
+  /// Parses an import statement, e.g. `import "math.txt" as m`. Like an include, the path must be
+  /// a string literal.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the import statement, its
+  ///   children are the path and the name of the module.
+  fn parse_import(&mut self) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::IMPORT)?);
+    output.append(ASTree::new(self.consume(TokenType::STRING)?));
+    self.consume(TokenType::AS)?;
+    output.append(ASTree::new(self.consume(TokenType::IDENTIFIER)?));
+    Ok(output)
+  }
+
   /// Parses a block of code enclosed in braces.
   ///
   /// # Arguments
@@ -266,20 +569,33 @@ impl Parser {
   ///
   /// * `Result<ASTree, String>` - A result containing the ASTree for the block
   fn parse_block(&mut self, name: String, scoped: bool) -> Result<ASTree, String> {
-    let mut output: ASTree = ASTree::new(Token::new(
-      TokenType::BLOCK(scoped),
-      name,
-      *self.peek().get_position(),
-    ));
+    let mut output: ASTree = ASTree::new(Token::at(TokenType::BLOCK(scoped), name, self.peek()));
     self.consume(TokenType::LBRACE)?;
 
     while !matches!(self.peek().get_type(), TokenType::RBRACE) {
-      output.append(self.parse_statement()?);
+      self.parse_statement_into(&mut output, true)?;
     }
     self.advance();
     Ok(output)
   }
 
+  /// Parses the parenthesized condition of an `if` or `while`, e.g. `(x < 5)`, or the count of a
+  /// `repeat`.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the condition, or an error
+  ///   message pointing at the `(` if the condition is empty.
+  fn parse_condition(&mut self) -> Result<ASTree, String> {
+    let lparen: Token = self.consume(TokenType::LPAREN)?;
+    if matches!(self.peek().get_type(), TokenType::RPAREN) {
+      return Err(format!("Empty condition at {}", lparen.get_location()));
+    }
+    let condition: ASTree = self.parse_expression()?;
+    self.consume(TokenType::RPAREN)?;
+    Ok(condition)
+  }
+
   /// Parses an if statement.
   ///
   /// # Returns
@@ -288,15 +604,13 @@ impl Parser {
   fn parse_if(&mut self) -> Result<ASTree, String> {
     let mut output: ASTree = ASTree::new(self.consume(TokenType::IF)?);
 
-    self.consume(TokenType::LPAREN)?;
-    output.append(self.parse_expression()?);
-    self.consume(TokenType::RPAREN)?;
+    output.append(self.parse_condition()?);
 
-    output.append(self.parse_block(format!("if_block"), true)?);
+    output.append(self.parse_block("if_block".to_string(), true)?);
 
     if matches!(self.peek().get_type(), TokenType::ELSE) {
       self.advance();
-      output.append(self.parse_block(format!("else_block"), true)?);
+      output.append(self.parse_block("else_block".to_string(), true)?);
     }
 
     Ok(output)
@@ -304,18 +618,203 @@ impl Parser {
 
   /// Parses a while loop.
   ///
+  /// # Arguments
+  ///
+  /// * `label` - The identifier labelling the loop, if any, appended as the loop's last child.
+  ///
   /// # Returns
   ///
   /// * `Result<ASTree, String>` - A result containing the ASTree for the while loop
-  fn parse_while(&mut self) -> Result<ASTree, String> {
+  fn parse_while(&mut self, label: Option<ASTree>) -> Result<ASTree, String> {
     let mut output: ASTree = ASTree::new(self.consume(TokenType::WHILE)?);
 
+    output.append(self.parse_condition()?);
+
+    output.append(self.parse_loop_body(label.as_ref(), "while_block")?);
+    self.parse_loop_else(&mut output, "while_else_block")?;
+
+    if let Some(label) = label {
+      output.append(label);
+    }
+    Ok(output)
+  }
+
+  /// Parses a C-style for loop, e.g. `for (i = 0; i < 10; i = i + 1) { ... }`.
+  ///
+  /// # Arguments
+  ///
+  /// * `label` - The identifier labelling the loop, if any, appended as the loop's last child.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the for loop, with the
+  ///   initialization, condition, step and body as its children, or an error message.
+  fn parse_for(&mut self, label: Option<ASTree>) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::FOR)?);
+
     self.consume(TokenType::LPAREN)?;
+    output.append(self.parse_statement()?);
+    self.consume(TokenType::SEMICOLON)?;
     output.append(self.parse_expression()?);
+    self.consume(TokenType::SEMICOLON)?;
+    output.append(self.parse_statement()?);
     self.consume(TokenType::RPAREN)?;
 
-    output.append(self.parse_block(format!("while_block"), true)?);
+    output.append(self.parse_loop_body(label.as_ref(), "for_block")?);
+    self.parse_loop_else(&mut output, "for_else_block")?;
+
+    if let Some(label) = label {
+      output.append(label);
+    }
+    Ok(output)
+  }
+
+  /// Parses a `loop` loop, e.g. `loop { ... }`, which runs until a `break` exits it.
+  ///
+  /// # Arguments
+  ///
+  /// * `label` - The identifier labelling the loop, if any, appended as the loop's last child.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the loop, with the body as its
+  ///   child, or an error message.
+  fn parse_loop(&mut self, label: Option<ASTree>) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::LOOP)?);
+    output.append(self.parse_loop_body(label.as_ref(), "loop_block")?);
+
+    if let Some(label) = label {
+      output.append(label);
+    }
+    Ok(output)
+  }
+
+  /// Parses a repeat loop, e.g. `repeat (3) { ... }`, which runs its body a given number of times.
+  ///
+  /// # Arguments
+  ///
+  /// * `label` - The identifier labelling the loop, if any, appended as the loop's last child.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the loop, with the count and
+  ///   body as its children, or an error message.
+  fn parse_repeat(&mut self, label: Option<ASTree>) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(TokenType::REPEAT)?);
+    output.append(self.parse_condition()?);
+    output.append(self.parse_loop_body(label.as_ref(), "repeat_block")?);
+
+    if let Some(label) = label {
+      output.append(label);
+    }
+    Ok(output)
+  }
+
+  /// Parses the body of a loop, within which `break` and `continue` may target the loop.
+  ///
+  /// # Arguments
+  ///
+  /// * `label` - The identifier labelling the loop, if any.
+  /// * `name` - The name of the body block.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the body, or an error message.
+  fn parse_loop_body(&mut self, label: Option<&ASTree>, name: &str) -> Result<ASTree, String> {
+    self
+      .loop_labels
+      .push(label.map(|label| label.get_token().get_value().clone()));
+    let body = self.parse_block(name.to_string(), true);
+    self.loop_labels.pop();
+    body
+  }
+
+  /// Parses the optional `else` block of a loop, run when the loop's body never ran, appending
+  /// it to the loop node.
+  ///
+  /// # Arguments
+  ///
+  /// * `output` - The loop node the block is appended to.
+  /// * `name` - The name of the block.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<(), String>` - Ok if there is no else block or it was parsed, or an error message.
+  fn parse_loop_else(&mut self, output: &mut ASTree, name: &str) -> Result<(), String> {
+    if matches!(self.peek().get_type(), TokenType::ELSE) {
+      self.advance();
+      output.append(self.parse_block(name.to_string(), true)?);
+    }
+    Ok(())
+  }
 
+  /// Parses a labeled loop, e.g. `outer: while (...) { ... }`.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the loop, or an error message
+  ///   if the label isn't followed by a loop or is already used by an enclosing loop.
+  fn parse_labeled_loop(&mut self) -> Result<ASTree, String> {
+    let label: ASTree = ASTree::new(self.consume(TokenType::IDENTIFIER)?);
+    self.consume(TokenType::COLON)?;
+
+    let name = label.get_token().get_value();
+    if self.loop_labels.contains(&Some(name.clone())) {
+      return Err(format!(
+        "Label '{}' at {} is already used by an enclosing loop",
+        name,
+        label.get_token().get_location()
+      ));
+    }
+    match self.peek().get_type() {
+      TokenType::WHILE => self.parse_while(Some(label)),
+      TokenType::FOR => self.parse_for(Some(label)),
+      TokenType::LOOP => self.parse_loop(Some(label)),
+      TokenType::REPEAT => self.parse_repeat(Some(label)),
+      _ => Err(format!(
+        "Expected a loop after label '{}', found {:?} at {}",
+        name,
+        self.peek().get_type(),
+        self.peek().get_location()
+      )),
+    }
+  }
+
+  /// Parses a `break` or `continue` statement, optionally followed by the label of the loop it
+  /// targets.
+  ///
+  /// # Arguments
+  ///
+  /// * `keyword` - The type of the keyword, BREAK or CONTINUE.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the statement, with the label
+  ///   as its child if any, or an error message if it isn't inside a matching loop.
+  fn parse_loop_control(&mut self, keyword: TokenType) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(self.consume(keyword)?);
+    if self.loop_labels.is_empty() {
+      return Err(format!(
+        "'{}' outside of a loop at {}",
+        output.get_token().get_value(),
+        output.get_token().get_location()
+      ));
+    }
+
+    // Statements following a break or continue in the same block are unreachable, so an
+    // identifier following one is always its label
+    if matches!(self.peek().get_type(), TokenType::IDENTIFIER) {
+      let label: ASTree = ASTree::new(self.consume(TokenType::IDENTIFIER)?);
+      let name = label.get_token().get_value();
+      if !self.loop_labels.contains(&Some(name.clone())) {
+        return Err(format!(
+          "Unknown label '{}' at {}",
+          name,
+          label.get_token().get_location()
+        ));
+      }
+      output.append(label);
+    }
     Ok(output)
   }
 
@@ -342,18 +841,100 @@ impl Parser {
     // Function body block shouldn't auto-create a new scope. During evaluation, parameters will be
     // need to be set within the function's scope, so ast::ASTree::eval_fn_call handles the scope
     // creation instead of ast::ASTree::eval_block
-    output.append(self.parse_block("fn_body_block".to_string(), false)?);
+    // Loops enclosing the definition can't be exited from within the function
+    let enclosing_loops = std::mem::take(&mut self.loop_labels);
+    let body = self.parse_block("fn_body_block".to_string(), false);
+    self.loop_labels = enclosing_loops;
+    output.append(body?);
     Ok(output)
   }
 
-  /// Parses a function call.
+  /// Checks whether the upcoming tokens start a lambda expression, either a single parameter or a
+  /// parenthesized parameter list followed by `=>`.
   ///
   /// # Returns
   ///
-  /// * `Result<ASTree, String>` - A result containing the ASTree for the function call
-  fn parse_fn_call(&mut self) -> Result<ASTree, String> {
-    let mut output: ASTree = ASTree::new(self.consume(TokenType::IDENTIFIER)?);
+  /// * `true` if a lambda expression starts at the current token, `false` otherwise.
+  fn is_lambda_start(&self) -> bool {
+    match self.peek().get_type() {
+      TokenType::IDENTIFIER => matches!(self.peek_ahead(1).get_type(), TokenType::ARROW),
+      TokenType::LPAREN => {
+        let mut n: usize = 1;
+        if matches!(self.peek_ahead(n).get_type(), TokenType::IDENTIFIER) {
+          n += 1;
+          while matches!(self.peek_ahead(n).get_type(), TokenType::COMMA)
+            && matches!(self.peek_ahead(n + 1).get_type(), TokenType::IDENTIFIER)
+          {
+            n += 2;
+          }
+        }
+        matches!(self.peek_ahead(n).get_type(), TokenType::RPAREN)
+          && matches!(self.peek_ahead(n + 1).get_type(), TokenType::ARROW)
+      }
+      _ => false,
+    }
+  }
+
+  /// Parses a lambda expression, e.g. `(a, b) => a + b`, `x => x * 2` or `x => { x * 2 }`.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the lambda, with the
+  ///   parameters and then the body as its children.
+  fn parse_lambda(&mut self) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(Token::at(
+      TokenType::LAMBDA,
+      "lambda".to_string(),
+      self.peek(),
+    ));
 
+    if matches!(self.peek().get_type(), TokenType::IDENTIFIER) {
+      output.append(ASTree::new(self.consume(TokenType::IDENTIFIER)?));
+    } else {
+      self.consume(TokenType::LPAREN)?;
+      if matches!(self.peek().get_type(), TokenType::IDENTIFIER) {
+        output.append(ASTree::new(self.consume(TokenType::IDENTIFIER)?));
+      }
+      while matches!(self.peek().get_type(), TokenType::COMMA) {
+        self.consume(TokenType::COMMA)?;
+        output.append(ASTree::new(self.consume(TokenType::IDENTIFIER)?));
+      }
+      self.consume(TokenType::RPAREN)?;
+    }
+    self.consume(TokenType::ARROW)?;
+
+    // Like a function body, the body doesn't create its own scope, ast::ASTree::eval_fn_call does.
+    // An expression body is wrapped in a block so both kinds of body are called the same way.
+    let enclosing_loops = std::mem::take(&mut self.loop_labels);
+    let body = if matches!(self.peek().get_type(), TokenType::LBRACE) {
+      self.parse_block("lambda_body_block".to_string(), false)
+    } else {
+      let mut block: ASTree = ASTree::new(Token::at(
+        TokenType::BLOCK(false),
+        "lambda_body_block".to_string(),
+        self.peek(),
+      ));
+      self.parse_expression().map(|expression| {
+        block.append(expression);
+        block
+      })
+    };
+    self.loop_labels = enclosing_loops;
+    output.append(body?);
+    Ok(output)
+  }
+
+  /// Parses the parenthesized argument list of a function call, appending each argument to the
+  /// call node.
+  ///
+  /// # Arguments
+  ///
+  /// * `output` - The call node the arguments are appended to.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<(), String>` - A result indicating success or an error message.
+  fn parse_call_args(&mut self, output: &mut ASTree) -> Result<(), String> {
     self.consume(TokenType::LPAREN)?;
     if !matches!(self.peek().get_type(), TokenType::RPAREN) {
       output.append(self.parse_expression()?);
@@ -363,10 +944,142 @@ impl Parser {
       output.append(self.parse_expression()?);
     }
     self.consume(TokenType::RPAREN)?;
+    Ok(())
+  }
+
+  /// Parses a function call.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the function call
+  fn parse_fn_call(&mut self) -> Result<ASTree, String> {
+    let mut output: ASTree = self.parse_call_name()?;
+    self.parse_call_args(&mut output)?;
+    Ok(output)
+  }
+
+  /// Parses the name of a called function into a call node, without its arguments.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the call node
+  fn parse_call_name(&mut self) -> Result<ASTree, String> {
+    let name: Token = self.consume(TokenType::IDENTIFIER)?;
+    Ok(ASTree::new(Token::at(
+      TokenType::CALL,
+      name.get_value().clone(),
+      &name,
+    )))
+  }
+
+  /// Parses an array literal, e.g. `[1, 2, 3]`.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the array literal
+  fn parse_array(&mut self) -> Result<ASTree, String> {
+    let mut output: ASTree = ASTree::new(Token::at(
+      TokenType::ARRAY,
+      "array".to_string(),
+      self.peek(),
+    ));
+
+    self.consume(TokenType::LBRACKET)?;
+    if !matches!(self.peek().get_type(), TokenType::RBRACKET) {
+      output.append(self.parse_expression()?);
+    }
+    while matches!(self.peek().get_type(), TokenType::COMMA) {
+      self.consume(TokenType::COMMA)?;
+      output.append(self.parse_expression()?);
+    }
+    self.consume(TokenType::RBRACKET)?;
 
     Ok(output)
   }
 
+  /// Parses an expression grouped by parentheses, e.g. `(1 + 2)` in `(1 + 2) * 3`. The group
+  /// produces no node of its own, the tree of the inner expression already encodes the grouping.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the inner expression, or an
+  ///   error if the parentheses are empty or unbalanced.
+  fn parse_group(&mut self) -> Result<ASTree, String> {
+    let lparen: Token = self.consume(TokenType::LPAREN)?;
+    if matches!(self.peek().get_type(), TokenType::RPAREN) {
+      return Err(format!(
+        "Empty parentheses at {}, expected an expression between them",
+        lparen.get_location()
+      ));
+    }
+    let expression: ASTree = self.parse_expression()?;
+    if !matches!(self.peek().get_type(), TokenType::RPAREN) {
+      return Err(format!(
+        "Unclosed '(' at {}, expected ')' before {}, found {:?}",
+        lparen.get_location(),
+        self.peek().get_location(),
+        self.peek().get_type()
+      ));
+    }
+    self.consume(TokenType::RPAREN)?;
+    Ok(expression)
+  }
+
+  /// Parses any method calls and index accesses following an operand, e.g. `"abc".len()` or
+  /// `arr[0]`.
+  /// A method call is syntactic sugar for a function call with the receiver as first argument,
+  /// so `receiver.name(args)` produces the same ASTree as `name(receiver, args)`.
+  ///
+  /// # Arguments
+  ///
+  /// * `operand` - The already parsed operand the postfix operations apply to.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<ASTree, String>` - A result containing the ASTree for the postfix chain
+  fn parse_postfix(&mut self, operand: ASTree) -> Result<ASTree, String> {
+    let mut output: ASTree = operand;
+    loop {
+      match self.peek().get_type() {
+        TokenType::DOT => {
+          self.consume(TokenType::DOT)?;
+          let name: Token = self.consume(TokenType::IDENTIFIER)?;
+          let mut call: ASTree = ASTree::new(Token::at(
+            TokenType::METHODCALL,
+            name.get_value().clone(),
+            &name,
+          ));
+          call.append(output);
+          self.parse_call_args(&mut call)?;
+          output = call;
+        }
+        TokenType::LPAREN => {
+          let mut apply: ASTree = ASTree::new(Token::at(
+            TokenType::APPLY,
+            "apply".to_string(),
+            self.peek(),
+          ));
+          apply.append(output);
+          self.parse_call_args(&mut apply)?;
+          output = apply;
+        }
+        TokenType::LBRACKET => {
+          let mut index: ASTree = ASTree::new(Token::at(
+            TokenType::INDEX,
+            "index".to_string(),
+            self.peek(),
+          ));
+          self.consume(TokenType::LBRACKET)?;
+          index.append(output);
+          index.append(self.parse_expression()?);
+          self.consume(TokenType::RBRACKET)?;
+          output = index;
+        }
+        _ => return Ok(output),
+      }
+    }
+  }
+
   /// Parses a single statement based on the current token.
   ///
   /// # Returns
@@ -375,17 +1088,58 @@ impl Parser {
   fn parse_statement(&mut self) -> Result<ASTree, String> {
     match self.peek().get_type() {
       TokenType::IF => self.parse_if(),
-      TokenType::WHILE => self.parse_while(),
+      TokenType::WHILE => self.parse_while(Option::None),
+      TokenType::FOR => self.parse_for(Option::None),
+      TokenType::LOOP => self.parse_loop(Option::None),
+      TokenType::REPEAT => self.parse_repeat(Option::None),
+      TokenType::BREAK => self.parse_loop_control(TokenType::BREAK),
+      TokenType::CONTINUE => self.parse_loop_control(TokenType::CONTINUE),
       TokenType::FN => self.parse_fn_def(),
-      TokenType::EOF => return Err("Attempted to parse EOF token".to_string()),
-      TokenType::LBRACE => self.parse_block(format!("sub_block"), true),
+      TokenType::GLOBAL => self.parse_keyword_assign(TokenType::GLOBAL),
+      TokenType::CONST => self.parse_keyword_assign(TokenType::CONST),
+      TokenType::THROW => self.parse_throw(),
+      TokenType::RETURN => self.parse_return(),
+      TokenType::INCLUDE | TokenType::IMPORT => Err(format!(
+        "Unexpected '{}' at {}, files can only be {} at the top level",
+        self.peek().get_value(),
+        self.peek().get_location(),
+        if matches!(self.peek().get_type(), TokenType::INCLUDE) {
+          "included"
+        } else {
+          "imported"
+        }
+      )),
+      TokenType::EOF => Err("Attempted to parse EOF token".to_string()),
+      TokenType::DOT => Err(format!(
+        "Unexpected '.' at {}, expected a value before it to call a function on, e.g. `x.f()`",
+        self.peek().get_location()
+      )),
+      TokenType::RPAREN => Err(format!(
+        "Unmatched ')' at {}, no '(' is open",
+        self.peek().get_location()
+      )),
+      TokenType::ARROW => Err(format!(
+        "Unexpected '=>' at {}, expected a lambda parameter list before it",
+        self.peek().get_location()
+      )),
+      TokenType::LBRACE => self.parse_block("sub_block".to_string(), true),
       TokenType::IDENTIFIER => {
-        if self.peek_next().is_some()
-          && matches!(self.peek_next().unwrap().get_type(), TokenType::ASSIGN)
-        {
+        if matches!(self.peek_ahead(1).get_type(), TokenType::COLON) {
+          self.parse_labeled_loop()
+        } else if matches!(self.peek_ahead(1).get_type(), TokenType::ASSIGN) {
           self.parse_assign()
+        } else if matches!(self.peek_ahead(1).get_type(), TokenType::COMMA) {
+          self.parse_destructuring_assign()
         } else {
-          self.parse_expression()
+          let expression: ASTree = self.parse_expression()?;
+          // Index accesses can also be assignment targets, e.g. `arr[0] = 5`
+          if matches!(expression.get_type(), TokenType::INDEX)
+            && matches!(self.peek().get_type(), TokenType::ASSIGN)
+          {
+            self.parse_assign_value(expression)
+          } else {
+            Ok(expression)
+          }
         }
       }
       _ => self.parse_expression(),
@@ -399,17 +1153,185 @@ impl Parser {
   ///
   /// # Returns
   ///
-  /// * `Result<ASTree>, String>` - A result containing the ASTree or an error
-  /// message.
-  pub fn parse(&mut self) -> Result<ASTree, String> {
+  /// * `Result<ASTree, Vec<String>>` - A result containing the ASTree or the error messages,
+  ///   more than one only when recovering from errors, see `set_max_errors`.
+  pub fn parse(&mut self) -> Result<ASTree, Vec<String>> {
     let mut output: ASTree = ASTree::new(Token::new(
       TokenType::BLOCK(true),
       String::from("global_block"),
       0,
+      1,
+      1,
     ));
+    self.incomplete = false;
     while !matches!(self.peek().get_type(), TokenType::EOF) {
-      output.append(self.parse_statement()?);
+      if let Err(error) = self.parse_statement_into(&mut output, false) {
+        // Errors that were recovered from make the input erroneous, however it continues. Only
+        // running out of tokens makes it incomplete, other errors at the end are still errors
+        self.incomplete = self.errors.is_empty()
+          && (error.contains("found EOF") || error.starts_with("Attempted to parse EOF token"));
+        self.errors.push(error);
+        break;
+      }
+    }
+    if !self.errors.is_empty() {
+      return Err(std::mem::take(&mut self.errors));
     }
     Ok(output)
   }
+
+  /// Parses a statement and appends it to a block. If the statement has an error and the error
+  /// cap isn't reached, the error is recorded and parsing skips to the next statement instead.
+  ///
+  /// # Arguments
+  ///
+  /// * `output` - The block the statement is appended to.
+  /// * `in_block` - Whether the statement is inside braces, so that recovery stops at their `}`.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<(), String>` - Ok if the statement was parsed or recovered from, or the error that
+  ///   stops parsing.
+  fn parse_statement_into(&mut self, output: &mut ASTree, in_block: bool) -> Result<(), String> {
+    // Included files bind into the global scope and imported modules are named by a global, so
+    // neither can appear inside a block
+    let statement: Result<ASTree, String> = match self.peek().get_type() {
+      TokenType::INCLUDE if !in_block => self.parse_include(),
+      TokenType::IMPORT if !in_block => self.parse_import(),
+      _ => self.parse_statement(),
+    };
+    match statement {
+      Ok(statement) => {
+        output.append(statement);
+        Ok(())
+      }
+      // There is nothing left to recover at the end of the tokens
+      Err(error)
+        if self.errors.len() + 1 >= self.max_errors
+          || matches!(self.peek().get_type(), TokenType::EOF) =>
+      {
+        Err(error)
+      }
+      Err(error) => {
+        self.errors.push(error);
+        self.synchronize(in_block);
+        Ok(())
+      }
+    }
+  }
+
+  /// Skips tokens after an error until the start of the next statement: after a `;`, before a
+  /// keyword starting a statement, an assignment or a labeled loop, or before the `}` closing the
+  /// current block. At least one token is skipped, so that parsing always makes progress.
+  ///
+  /// # Arguments
+  ///
+  /// * `in_block` - Whether the error is inside braces, a `}` is skipped at top level.
+  fn synchronize(&mut self, in_block: bool) {
+    if !(in_block && matches!(self.peek().get_type(), TokenType::RBRACE)) {
+      self.advance();
+    }
+    loop {
+      match self.peek().get_type() {
+        TokenType::SEMICOLON => {
+          self.advance();
+          return;
+        }
+        TokenType::RBRACE if in_block => return,
+        TokenType::EOF
+        | TokenType::IF
+        | TokenType::WHILE
+        | TokenType::FOR
+        | TokenType::LOOP
+        | TokenType::REPEAT
+        | TokenType::FN
+        | TokenType::GLOBAL
+        | TokenType::CONST
+        | TokenType::THROW
+        | TokenType::RETURN
+        | TokenType::INCLUDE
+        | TokenType::IMPORT
+        | TokenType::BREAK
+        | TokenType::CONTINUE => return,
+        TokenType::IDENTIFIER
+          if matches!(
+            self.peek_ahead(1).get_type(),
+            TokenType::ASSIGN | TokenType::COLON
+          ) =>
+        {
+          return;
+        }
+        _ => {
+          self.advance();
+        }
+      }
+    }
+  }
+}
+
+impl Default for Parser {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lexer::Lexer;
+
+  /// Creates a parser over the tokens of an input that is expected to be valid.
+  fn parser_for(input: &str) -> Parser {
+    let mut lexer: Lexer = Lexer::new();
+    lexer.set_input(input.to_string());
+    let mut parser: Parser = Parser::new();
+    parser.set_tokens(lexer.tokenize().expect("The input should lex"));
+    parser
+  }
+
+  #[test]
+  fn advancing_a_clone_leaves_the_original_unchanged() {
+    let mut original: Parser = parser_for("a = 1");
+    let mut clone: Parser = original.clone();
+
+    assert_eq!(clone.advance().get_value(), "a");
+    assert_eq!(clone.peek().get_type(), &TokenType::ASSIGN);
+    assert_eq!(original.peek().get_value(), "a");
+
+    // The original parses the whole input from its own position
+    assert!(original.parse().is_ok());
+    assert_eq!(clone.peek().get_type(), &TokenType::ASSIGN);
+  }
+
+  #[test]
+  fn input_ending_early_is_incomplete() {
+    for input in ["fn f() {", "x = [1", "x = 1 +"] {
+      let mut parser = parser_for(input);
+      assert!(parser.parse().is_err(), "{input}");
+      assert!(parser.is_incomplete(), "{input}");
+    }
+  }
+
+  #[test]
+  fn error_at_the_end_of_the_input_is_not_incomplete() {
+    let mut parser = parser_for("break");
+    let errors = parser.parse().map(|_| ()).unwrap_err();
+    assert!(
+      errors[0].starts_with("'break' outside of a loop"),
+      "{errors:?}"
+    );
+    assert!(!parser.is_incomplete());
+  }
+
+  #[test]
+  fn dot_without_a_receiver_is_an_error() {
+    assert_eq!(
+      parser_for(".f()").parse().map(|_| ()),
+      Err(vec![
+        "Unexpected '.' at line 1, column 1, expected a value before it to call a function on, e.g. `x.f()`"
+          .to_string()
+      ])
+    );
+    assert!(parser_for("x.f()").parse().is_ok());
+  }
 }
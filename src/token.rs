@@ -1,7 +1,7 @@
 //! This module defines the core token structures and types used by the lexer and parser.
 //!
 //! It provides the `TokenType` enum for classifying tokens,
-//! and the `Token` struct for encapsulating token data, including its type, value, and position in the source code.
+//! and the `Token` struct for encapsulating token data, including its type, value, and location in the source code.
 
 /// Represents the different types of tokens that can be identified by the lexer.
 #[derive(PartialEq, Clone, Debug)]
@@ -12,20 +12,54 @@ pub enum TokenType {
   IDENTIFIER,
   /// String literal.
   STRING,
+  /// Boolean literal, `true` or `false`.
+  BOOL,
   /// Binary operator (e.g., +, -, *, /).
   BINARYOP,
+  /// Unary minus '-', negating a number. The parser turns a '-' found where an operand is
+  /// expected into this token, the lexer only produces binary operators.
+  UNARYOP,
   /// Assignment operator (e.g., =).
   ASSIGN,
+  /// Arrow '=>', separating a lambda's parameters from its body.
+  ARROW,
+  /// Assignment expression operator ':=', assigning and evaluating to the assigned value.
+  WALRUS,
   /// 'if' keyword.
   IF,
   /// 'while' keyword.
   WHILE,
   /// 'for' keyword.
   FOR,
+  /// 'loop' keyword, for a loop only exited by `break`.
+  LOOP,
+  /// 'repeat' keyword, for a loop running its body a given number of times.
+  REPEAT,
   /// 'else' keyword.
   ELSE,
   /// 'fn' keyword for function declaration.
   FN,
+  /// 'not' keyword, prefix logical negation.
+  NOT,
+  /// 'global' keyword, assignment to the global scope.
+  GLOBAL,
+  /// 'const' keyword, declaration of a constant.
+  CONST,
+  /// 'throw' keyword, raises a runtime error carrying a value.
+  THROW,
+  /// 'return' keyword, ends the enclosing function call, or the program at the top level.
+  RETURN,
+  /// 'break' keyword, exits the enclosing loop, or the loop with the given label.
+  BREAK,
+  /// 'include' keyword, evaluates another file's statements in the global scope.
+  INCLUDE,
+  /// 'import' keyword, evaluates another file's statements into a module.
+  IMPORT,
+  /// 'as' keyword, naming an imported module.
+  AS,
+  /// 'continue' keyword, skips to the next iteration of the enclosing loop, or of the loop with
+  /// the given label.
+  CONTINUE,
   /// Left parenthesis '('.
   LPAREN,
   /// Right parenthesis ')'.
@@ -34,11 +68,37 @@ pub enum TokenType {
   LBRACE,
   /// Right brace '}'.
   RBRACE,
+  /// Left bracket '['.
+  LBRACKET,
+  /// Right bracket ']'.
+  RBRACKET,
   /// Comma ','.
   COMMA,
+  /// Colon ':', following the label of a loop.
+  COLON,
+  /// Semicolon ';', separating the clauses of a for loop.
+  SEMICOLON,
+  /// Dot '.', between the receiver of a method call and the called function, e.g. `m.f(x)`.
+  DOT,
   /// A block of code. Boolean values indicates whether the block should automatically enter a new
   /// scope when evaluated at runtime, true = new scope, false = no new scope.
   BLOCK(bool),
+  /// An array literal, its elements are the children of the node.
+  ARRAY,
+  /// An index access, its children are the indexed value and the index.
+  INDEX,
+  /// A function call, the value is the name of the called function and its children are the
+  /// arguments.
+  CALL,
+  /// A method call, e.g. `arr.len()` or `m.sqrt(4)`, the value is the name of the called function
+  /// and its children are the receiver and the arguments. The receiver is passed as the first
+  /// argument, unless it is an imported module whose function is called instead.
+  METHODCALL,
+  /// A call of the function value an expression evaluates to, e.g. `make_adder(1)(2)`, its
+  /// children are the called expression and the arguments.
+  APPLY,
+  /// A lambda expression, e.g. `x => x * 2`, its children are the parameters and the body.
+  LAMBDA,
   /// End of file.
   EOF,
 }
@@ -52,6 +112,10 @@ pub struct Token {
   value: String,
   /// The position of the token in the source code.
   pos: usize,
+  /// The line of the token in the source code, starting at 1.
+  line: usize,
+  /// The column of the token in its line, starting at 1.
+  column: usize,
 }
 
 impl Token {
@@ -62,14 +126,42 @@ impl Token {
   /// * `token_type` - The type of the token.
   /// * `value` - The string value of the token.
   /// * `position` - The position of the token in the source code.
-  pub fn new(token_type: TokenType, value: String, position: usize) -> Token {
+  /// * `line` - The line of the token, starting at 1.
+  /// * `column` - The column of the token in its line, starting at 1.
+  pub fn new(
+    token_type: TokenType,
+    value: String,
+    position: usize,
+    line: usize,
+    column: usize,
+  ) -> Token {
     Token {
-      token_type: token_type,
-      value: value,
+      token_type,
+      value,
       pos: position,
+      line,
+      column,
     }
   }
 
+  /// Creates a new `Token` at the location of another one, for nodes the parser adds to the tree
+  /// such as blocks and calls.
+  ///
+  /// # Arguments
+  ///
+  /// * `token_type` - The type of the token.
+  /// * `value` - The string value of the token.
+  /// * `location` - The token whose position, line and column are reused.
+  pub fn at(token_type: TokenType, value: String, location: &Token) -> Token {
+    Token::new(
+      token_type,
+      value,
+      location.pos,
+      location.line,
+      location.column,
+    )
+  }
+
   /// Returns a reference to the value of the token.
   pub fn get_value(&self) -> &String {
     &self.value
@@ -84,4 +176,19 @@ impl Token {
   pub fn get_position(&self) -> &usize {
     &self.pos
   }
+
+  /// Returns the line of the token in the source code, starting at 1.
+  pub fn get_line(&self) -> usize {
+    self.line
+  }
+
+  /// Returns the column of the token in its line, starting at 1.
+  pub fn get_column(&self) -> usize {
+    self.column
+  }
+
+  /// Returns the location of the token as worded in error messages, e.g. `line 3, column 12`.
+  pub fn get_location(&self) -> String {
+    format!("line {}, column {}", self.line, self.column)
+  }
 }
@@ -0,0 +1,103 @@
+//! Visitor pattern for walking an Abstract Syntax Tree (AST).
+//!
+//! This module defines the `Visitor` trait, letting tools such as linters or formatters walk a
+//! parsed tree through `ASTree::accept` without depending on its internal representation.
+
+use crate::ast::ASTree;
+
+/// A visitor over the nodes of an ASTree, with one method per kind of node.
+///
+/// `ASTree::accept` calls the method matching each node before visiting its children, in order.
+/// Every method defaults to doing nothing, so a visitor only overrides the nodes it cares about.
+pub trait Visitor {
+  /// Visits a numeric literal.
+  fn visit_numeric(&mut self, _node: &ASTree) {}
+
+  /// Visits a string literal.
+  fn visit_string(&mut self, _node: &ASTree) {}
+
+  /// Visits a boolean literal.
+  fn visit_bool(&mut self, _node: &ASTree) {}
+
+  /// Visits a variable access.
+  fn visit_identifier(&mut self, _node: &ASTree) {}
+
+  /// Visits a function call, its children are the arguments.
+  fn visit_fn_call(&mut self, _node: &ASTree) {}
+
+  /// Visits a call of a function value, its children are the called expression and the
+  /// arguments.
+  fn visit_apply(&mut self, _node: &ASTree) {}
+
+  /// Visits a binary operation, its children are the left and right operands.
+  fn visit_binary_op(&mut self, _node: &ASTree) {}
+
+  /// Visits a logical negation, its child is the operand.
+  fn visit_not(&mut self, _node: &ASTree) {}
+
+  /// Visits a numeric negation, its child is the operand.
+  fn visit_unary_op(&mut self, _node: &ASTree) {}
+
+  /// Visits an assignment, its children are the target and the value.
+  fn visit_assign(&mut self, _node: &ASTree) {}
+
+  /// Visits an assignment expression, its children are the identifier and the value.
+  fn visit_walrus(&mut self, _node: &ASTree) {}
+
+  /// Visits a global assignment, its children are the identifier and the value.
+  fn visit_global(&mut self, _node: &ASTree) {}
+
+  /// Visits a constant declaration, its children are the identifier and the value.
+  fn visit_const(&mut self, _node: &ASTree) {}
+
+  /// Visits a throw statement, its child is the thrown expression.
+  fn visit_throw(&mut self, _node: &ASTree) {}
+
+  /// Visits a return statement, its child is the returned expression, if any.
+  fn visit_return(&mut self, _node: &ASTree) {}
+
+  /// Visits an include statement, its child is the path of the included file.
+  fn visit_include(&mut self, _node: &ASTree) {}
+
+  /// Visits an import statement, its children are the path of the imported file and the name of
+  /// the module.
+  fn visit_import(&mut self, _node: &ASTree) {}
+
+  /// Visits an if statement, its children are the condition, then-block and optional else-block.
+  fn visit_if(&mut self, _node: &ASTree) {}
+
+  /// Visits a while loop, its children are the condition, the body, the optional else block and
+  /// the optional label.
+  fn visit_while(&mut self, _node: &ASTree) {}
+
+  /// Visits a for loop, its children are the initialization, condition, step, body, optional else
+  /// block and optional label.
+  fn visit_for(&mut self, _node: &ASTree) {}
+
+  /// Visits a `loop` loop, its children are the body and the optional label.
+  fn visit_loop(&mut self, _node: &ASTree) {}
+
+  /// Visits a repeat loop, its children are the count, the body and the optional label.
+  fn visit_repeat(&mut self, _node: &ASTree) {}
+
+  /// Visits a break statement, its child is the optional label of the targeted loop.
+  fn visit_break(&mut self, _node: &ASTree) {}
+
+  /// Visits a continue statement, its child is the optional label of the targeted loop.
+  fn visit_continue(&mut self, _node: &ASTree) {}
+
+  /// Visits a function definition, its children are the name, parameters and body.
+  fn visit_fn_def(&mut self, _node: &ASTree) {}
+
+  /// Visits a lambda expression, its children are the parameters and body.
+  fn visit_lambda(&mut self, _node: &ASTree) {}
+
+  /// Visits a block, its children are its statements.
+  fn visit_block(&mut self, _node: &ASTree) {}
+
+  /// Visits an array literal, its children are the elements.
+  fn visit_array(&mut self, _node: &ASTree) {}
+
+  /// Visits an index access, its children are the indexed value and the index.
+  fn visit_index(&mut self, _node: &ASTree) {}
+}
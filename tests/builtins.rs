@@ -0,0 +1,269 @@
+//! Tests of the builtin functions, run as whole programs.
+
+mod common;
+
+use common::{error_message, eval};
+
+#[test]
+fn sort_orders_integers_and_floats_together() {
+  assert_eq!(eval("sort([3, 1.5, 2])"), "[1.5, 2, 3]");
+  assert_eq!(
+    error_message("sort([1, \"a\"])"),
+    "Builtin 'sort' cannot compare INTEGER(1) with STRING(\"a\"), at line 1, column 1"
+  );
+}
+
+#[test]
+fn parse_float_parses_numbers_and_returns_null_otherwise() {
+  assert_eq!(eval("parse_float(\"2.5\")"), "2.5");
+  assert_eq!(eval("parse_float(\"3\")"), "3.0");
+  assert_eq!(eval("parse_float(\"abc\")"), "null");
+  assert_eq!(eval("parse_float(\"\")"), "null");
+  assert!(error_message("parse_float(1)").starts_with("Builtin 'parse_float' expects a string"));
+}
+
+#[test]
+fn parse_int_returns_null_on_invalid_input() {
+  assert_eq!(eval("parse_int(\"42\")"), "42");
+  assert_eq!(eval("parse_int(\"4.2\")"), "null");
+}
+
+#[test]
+fn case_conversion_handles_ascii_and_unicode() {
+  assert_eq!(eval("to_upper(\"abc1\")"), "ABC1");
+  assert_eq!(eval("to_lower(\"ABC1\")"), "abc1");
+  assert_eq!(eval("to_upper(\"ä\")"), "Ä");
+  assert_eq!(eval("to_lower(\"Ä\")"), "ä");
+  assert_eq!(eval("to_upper(\"\")"), "");
+}
+
+#[test]
+fn eq_ignore_case_compares_without_case() {
+  assert_eq!(eval("eq_ignore_case(\"Hello\", \"hELLO\")"), "true");
+  assert_eq!(eval("eq_ignore_case(\"Ä\", \"ä\")"), "true");
+  assert_eq!(eval("eq_ignore_case(\"a\", \"b\")"), "false");
+}
+
+#[test]
+fn is_empty_checks_arrays_and_strings() {
+  assert_eq!(eval("is_empty([])"), "true");
+  assert_eq!(eval("is_empty([1])"), "false");
+  assert_eq!(eval("is_empty(\"\")"), "true");
+  assert_eq!(eval("is_empty(\"a\")"), "false");
+}
+
+#[test]
+fn first_and_last_of_a_single_element_are_that_element() {
+  assert_eq!(eval("first([7])"), "7");
+  assert_eq!(eval("last([7])"), "7");
+  assert_eq!(eval("first(\"é\")"), "é");
+  assert_eq!(eval("last(\"é\")"), "é");
+  assert_eq!(eval("first([1, 2, 3]) + last([1, 2, 3])"), "4");
+}
+
+#[test]
+fn first_and_last_of_an_empty_collection_are_errors() {
+  assert_eq!(
+    error_message("first([])"),
+    "Builtin 'first' called on an empty array, at line 1, column 1"
+  );
+  assert_eq!(
+    error_message("last([])"),
+    "Builtin 'last' called on an empty array, at line 1, column 1"
+  );
+  assert_eq!(
+    error_message("first(\"\")"),
+    "Builtin 'first' called on an empty string, at line 1, column 1"
+  );
+}
+
+#[test]
+fn zip_truncates_to_the_shorter_array() {
+  assert_eq!(
+    eval("zip([1, 2, 3], [\"a\", \"b\"])"),
+    "[[1, \"a\"], [2, \"b\"]]"
+  );
+  assert_eq!(eval("zip([1], [true, false])"), "[[1, true]]");
+  assert_eq!(eval("zip([], [1, 2])"), "[]");
+  assert_eq!(eval("zip([1, 2], [])"), "[]");
+  assert!(error_message("zip([1], 2)").starts_with("Builtin 'zip' expects two arrays"));
+}
+
+#[test]
+fn enumerate_pairs_elements_with_their_index() {
+  assert_eq!(
+    eval("enumerate([\"a\", \"b\"])"),
+    "[[0, \"a\"], [1, \"b\"]]"
+  );
+  assert_eq!(eval("enumerate([])"), "[]");
+  assert!(error_message("enumerate(\"ab\")").starts_with("Builtin 'enumerate' expects an array"));
+}
+
+#[test]
+fn sum_and_product_of_an_empty_array_are_their_identity() {
+  assert_eq!(eval("sum([])"), "0");
+  assert_eq!(eval("product([])"), "1");
+}
+
+#[test]
+fn sum_and_product_become_floats_once_an_element_is_a_float() {
+  assert_eq!(eval("sum([1, 2, 3])"), "6");
+  assert_eq!(eval("sum([1, 2.5])"), "3.5");
+  assert_eq!(eval("sum([0.5, 1])"), "1.5");
+  assert_eq!(eval("product([2, 1.5, 2])"), "6.0");
+  assert_eq!(eval("product([2.0])"), "2.0");
+  assert!(error_message("sum([1, \"a\"])").starts_with("Builtin 'sum' expects numbers"));
+}
+
+// The boundaries are those of the default 32-bit integers
+#[cfg(not(feature = "int64"))]
+#[test]
+fn sum_and_product_wrap_around_on_integer_overflow() {
+  assert_eq!(eval("sum([2147483647, 1])"), "-2147483648");
+  assert_eq!(eval("product([65536, 65536])"), "0");
+}
+
+#[test]
+fn env_set_and_env_get_round_trip_a_value() {
+  assert_eq!(eval("env_set(\"x\", [1, 2])\nenv_get(\"x\")"), "[1, 2]");
+  assert_eq!(eval("env_set(\"x\", 5)\nx + 1"), "6");
+  assert_eq!(eval("y = \"a\"\nenv_get(\"y\")"), "a");
+  assert_eq!(eval("env_set(\"été\", 1)\nété"), "1");
+}
+
+#[test]
+fn env_get_of_an_unset_variable_is_null() {
+  assert_eq!(eval("env_get(\"missing\")"), "null");
+  // A caller's locals are hidden from a function, like identifiers
+  assert_eq!(
+    eval("fn peek() { env_get(\"secret\") }\nfn caller() { secret = 1\npeek() }\ncaller()"),
+    "null"
+  );
+}
+
+#[test]
+fn env_builtins_reject_invalid_names_and_constants() {
+  assert!(
+    error_message("env_get(\"1x\")").starts_with("Builtin 'env_get' expects a variable name")
+  );
+  assert!(
+    error_message("env_set(\"a b\", 1)").starts_with("Builtin 'env_set' expects a variable name")
+  );
+  assert!(error_message("env_get(1)").starts_with("Builtin 'env_get' expects a variable name"));
+  assert_eq!(
+    error_message("const c = 1\nenv_set(\"c\", 2)"),
+    "Cannot reassign constant 'c', at line 2, column 1"
+  );
+}
+
+#[test]
+fn apply_calls_a_function_with_an_array_of_arguments() {
+  assert_eq!(
+    eval("fn add(a, b) { a + b }\napply(add, [1, 2]) == 3"),
+    "true"
+  );
+  assert_eq!(eval("apply((x) => x * 2, [21])"), "42");
+  assert_eq!(
+    error_message("fn add(a, b) { a + b }\napply(add, [1])"),
+    "Function expects 2 argument(s), found 1, at line 2, column 1"
+  );
+  assert!(
+    error_message("apply(1, [1])")
+      .starts_with("Builtin 'apply' expects a function as its first argument")
+  );
+}
+
+#[test]
+fn trim_removes_tabs_and_newlines() {
+  assert_eq!(eval("trim(\"\\t\\n a b \\r\\n\")"), "a b");
+  assert_eq!(eval("trim_start(\"\\t\\na\\n\")"), "a\n");
+  assert_eq!(eval("trim_end(\"\\ta\\n\\t\")"), "\ta");
+  assert_eq!(eval("trim(\" \\t\\n\")"), "");
+}
+
+#[test]
+fn min_by_and_max_by_compare_the_keys() {
+  let people = "people = [[\"ann\", 31], [\"bob\", 25], [\"cy\", 40], [\"di\", 25]]\n";
+  assert_eq!(
+    eval(&format!("{people}min_by(people, p => p[1])")),
+    "[\"bob\", 25]"
+  );
+  assert_eq!(
+    eval(&format!("{people}max_by(people, p => p[1])")),
+    "[\"cy\", 40]"
+  );
+  assert_eq!(
+    eval(&format!("{people}max_by(people, p => len(p[0]))")),
+    "[\"ann\", 31]"
+  );
+  assert_eq!(eval("min_by([], x => x)"), "null");
+  assert_eq!(eval("fn negate(x) { -x }\nmin_by([1, 3, 2], negate)"), "3");
+}
+
+#[test]
+fn gcd_and_lcm_of_small_integers() {
+  assert_eq!(eval("gcd(12, 18) == 6"), "true");
+  assert_eq!(eval("gcd(-12, 18)"), "6");
+  assert_eq!(eval("gcd(0, 0)"), "0");
+  assert_eq!(eval("lcm(4, 6)"), "12");
+  assert_eq!(eval("lcm(0, 6)"), "0");
+}
+
+#[test]
+fn pow_mod_requires_a_positive_modulus() {
+  assert_eq!(eval("pow_mod(3, 4, 5)"), "1");
+  assert_eq!(
+    error_message("pow_mod(2, 10, 0)"),
+    "Builtin 'pow_mod' expects a positive modulus, found 0, at line 1, column 1"
+  );
+  assert!(
+    error_message("pow_mod(2, 10, -3)").starts_with("Builtin 'pow_mod' expects a positive modulus")
+  );
+}
+
+#[test]
+fn chars_splits_by_unicode_scalar_value() {
+  assert_eq!(
+    eval("chars(\"héllo\")"),
+    "[\"h\", \"é\", \"l\", \"l\", \"o\"]"
+  );
+  assert_eq!(eval("len(chars(\"日本語\"))"), "3");
+  assert_eq!(eval("chars(\"🦀!\")"), "[\"🦀\", \"!\"]");
+  assert_eq!(eval("chars(\"\")"), "[]");
+  assert!(error_message("chars(1)").starts_with("Builtin 'chars' expects a string"));
+}
+
+#[test]
+fn join_separates_strings_and_displays_other_values() {
+  assert_eq!(eval("join([\"a\", \"b\", \"c\"], \", \")"), "a, b, c");
+  assert_eq!(eval("join([\"ab\"], \"-\")"), "ab");
+  assert_eq!(
+    eval("join([1, 2.5, true, [\"x\"]], \" \")"),
+    "1 2.5 true [\"x\"]"
+  );
+  assert_eq!(eval("join([\"a\", \"b\"], \"\")"), "ab");
+}
+
+#[test]
+fn join_of_an_empty_array_is_an_empty_string() {
+  assert_eq!(eval("join([], \", \")"), "");
+  assert_eq!(eval("len(join([], \"-\"))"), "0");
+}
+
+#[test]
+fn join_requires_an_array_and_a_string() {
+  assert_eq!(
+    error_message("join(\"ab\", \",\")"),
+    "Builtin 'join' expects an array and a string, found STRING(\"ab\") and STRING(\",\"), at line 1, column 1"
+  );
+  assert!(
+    error_message("join([1], 2)").starts_with("Builtin 'join' expects an array and a string")
+  );
+}
+
+#[test]
+fn pushing_pieces_then_joining_builds_a_large_string() {
+  let code =
+    "parts = []\nfor (i = 0; i < 20000; i = i + 1) { push(parts, \"ab\") }\nlen(join(parts, \"\"))";
+  assert_eq!(eval(code), "40000");
+}
@@ -0,0 +1,129 @@
+//! Tests of the command line interpreter, running its binary.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the interpreter on a program read from stdin.
+///
+/// # Arguments
+///
+/// * `args` - The command line options, `-` is added after them to read stdin.
+/// * `code` - The source code of the program.
+///
+/// # Returns
+///
+/// * `Output` - The exit status and outputs of the interpreter.
+fn run_cli(args: &[&str], code: &str) -> Output {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+    .args(args)
+    .arg("-")
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .expect("The interpreter should start");
+  // The interpreter exits without reading stdin when its arguments are invalid, so the write may
+  // fail with a broken pipe
+  let _ = child
+    .stdin
+    .take()
+    .expect("stdin is piped")
+    .write_all(code.as_bytes());
+  child
+    .wait_with_output()
+    .expect("The interpreter should exit")
+}
+
+/// Returns the standard error output of the interpreter.
+fn stderr(output: &Output) -> String {
+  String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn invalid_option_values_are_reported_without_panicking() {
+  for (args, message) in [
+    (
+      &["--max-steps", "abc"][..],
+      "Error: Invalid value for --max-steps: abc, see --help for usage\n",
+    ),
+    (
+      &["--timeout", "-5"][..],
+      "Error: Invalid value for --timeout: -5, see --help for usage\n",
+    ),
+    (
+      &["--unknown"][..],
+      "Error: Unknown option: --unknown, see --help for usage\n",
+    ),
+    (
+      &["extra.txt"][..],
+      "Error: Expected at most one input file, found 2, see --help for usage\n",
+    ),
+  ] {
+    let output: Output = run_cli(args, "1");
+    assert_eq!(output.status.code(), Some(1), "{args:?}");
+    assert_eq!(stderr(&output), message);
+  }
+}
+
+#[test]
+fn missing_option_value_is_reported_without_panicking() {
+  let output: Output = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+    .arg("--max-steps")
+    .output()
+    .expect("The interpreter should run");
+  assert_eq!(output.status.code(), Some(1));
+  assert_eq!(
+    stderr(&output),
+    "Error: Missing value for --max-steps, see --help for usage\n"
+  );
+}
+
+#[test]
+fn step_limit_stops_the_program_with_an_error() {
+  let output: Output = run_cli(&["--max-steps", "100"], "x = 0\nloop { x = x + 1 }");
+  assert_eq!(output.status.code(), Some(1));
+  assert!(
+    stderr(&output).contains("Error during runtime: Step limit of 100 exceeded"),
+    "{}",
+    stderr(&output)
+  );
+}
+
+#[test]
+fn timeout_stops_the_program_with_an_error() {
+  let output: Output = run_cli(&["--timeout", "50"], "x = 0\nloop { x = x + 1 }");
+  assert_eq!(output.status.code(), Some(1));
+  assert!(
+    stderr(&output).contains("Error during runtime: Time limit of 50 ms exceeded"),
+    "{}",
+    stderr(&output)
+  );
+}
+
+#[test]
+fn non_utf8_file_is_reported_with_the_offending_byte() {
+  let path = std::env::temp_dir().join(format!("interpreter_non_utf8_{}.txt", std::process::id()));
+  std::fs::write(&path, b"x = 1\n\xff\n").expect("The file should be written");
+  let output: Output = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+    .arg(&path)
+    .output()
+    .expect("The interpreter should run");
+  std::fs::remove_file(&path).expect("The file should be removed");
+
+  assert_eq!(output.status.code(), Some(1));
+  assert_eq!(
+    stderr(&output),
+    format!("Error: {} is not valid UTF-8 at byte 6\n", path.display())
+  );
+}
+
+#[test]
+fn pprint_writes_floats_with_a_decimal_point() {
+  let output: Output = run_cli(&[], "pprint(1.0)\npprint(0.1)\npprint(100000.0 * 100000.0)");
+  assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+  assert!(
+    String::from_utf8_lossy(&output.stdout).ends_with("1.0\n0.1\n10000000000.0\n"),
+    "{}",
+    String::from_utf8_lossy(&output.stdout)
+  );
+}
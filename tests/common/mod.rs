@@ -0,0 +1,53 @@
+//! Helpers shared by the integration tests, which run whole programs through the library.
+
+// Each test file only uses some of the helpers
+#![allow(dead_code)]
+
+use interpreter::InterpretError;
+
+/// Runs a program that is expected to succeed.
+///
+/// # Arguments
+///
+/// * `code` - The source code of the program.
+///
+/// # Returns
+///
+/// * `String` - The program's final value, formatted the way the REPL prints it.
+pub fn eval(code: &str) -> String {
+  match interpreter::run(code) {
+    Ok(value) => value.to_string(),
+    Err(error) => panic!("{code:?} should run, failed with: {error}"),
+  }
+}
+
+/// Runs a program that is expected to fail.
+///
+/// # Arguments
+///
+/// * `code` - The source code of the program.
+///
+/// # Returns
+///
+/// * `InterpretError` - The error stopping the program.
+pub fn eval_error(code: &str) -> InterpretError {
+  match interpreter::run(code) {
+    Ok(value) => panic!("{code:?} should fail, returned: {value}"),
+    Err(error) => error,
+  }
+}
+
+/// Runs a program that is expected to fail with a single error.
+///
+/// # Arguments
+///
+/// * `code` - The source code of the program.
+///
+/// # Returns
+///
+/// * `String` - The message of the error.
+pub fn error_message(code: &str) -> String {
+  let error: InterpretError = eval_error(code);
+  assert_eq!(error.get_errors().len(), 1, "{error}");
+  error.get_errors()[0].get_message().clone()
+}
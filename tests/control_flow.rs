@@ -0,0 +1,137 @@
+//! Tests of conditionals and loops, run as whole programs.
+
+mod common;
+
+use common::{error_message, eval, eval_error};
+use interpreter::context::Context;
+
+#[test]
+fn for_counter_does_not_outlive_the_loop() {
+  assert_eq!(
+    error_message("for (i = 0; i < 3; i = i + 1) {}\ni"),
+    "Attempted to access unset identifier: 'i', at line 2, column 1"
+  );
+}
+
+#[test]
+fn for_counter_mutations_persist_across_iterations() {
+  assert_eq!(
+    eval("total = 0\nfor (i = 0; i < 4; i = i + 1) { total = total + i }\ntotal"),
+    "6"
+  );
+  // Skipping ahead in the body affects the next condition check
+  assert_eq!(
+    eval("count = 0\nfor (i = 0; i < 10; i = i + 1) { i = i + 1\ncount = count + 1 }\ncount"),
+    "5"
+  );
+}
+
+#[test]
+fn for_counter_declared_outside_the_loop_keeps_its_final_value() {
+  assert_eq!(eval("i = 0\nfor (i = 0; i < 3; i = i + 1) {}\ni"), "3");
+}
+
+#[test]
+fn loop_else_runs_when_the_body_never_ran() {
+  assert_eq!(
+    eval(
+      "result = \"\"\nwhile (false) { result = \"ran\" } else { result = \"never ran\" }\nresult"
+    ),
+    "never ran"
+  );
+  assert_eq!(
+    eval(
+      "result = \"\"\nfor (i = 0; i < 0; i = i + 1) { result = \"ran\" } else { result = \"never ran\" }\nresult"
+    ),
+    "never ran"
+  );
+}
+
+#[test]
+fn loop_else_is_skipped_when_the_body_ran() {
+  assert_eq!(
+    eval(
+      "result = \"\"\nx = 0\nwhile (x < 2) { x = x + 1\nresult = \"ran\" } else { result = \"never ran\" }\nresult"
+    ),
+    "ran"
+  );
+  assert_eq!(
+    eval(
+      "result = \"\"\nfor (i = 0; i < 2; i = i + 1) { result = \"ran\" } else { result = \"never ran\" }\nresult"
+    ),
+    "ran"
+  );
+}
+
+#[test]
+fn empty_condition_is_reported_at_its_parenthesis() {
+  assert_eq!(
+    error_message("if () {}"),
+    "Empty condition at line 1, column 4"
+  );
+  assert_eq!(
+    error_message("while () {}"),
+    "Empty condition at line 1, column 7"
+  );
+  assert_eq!(eval_error("if () {}").get_stage(), "parsing");
+}
+
+/// Runs a program in a context that already has a scope, returning the scope depth afterwards.
+fn scope_depth_after(code: &str) -> usize {
+  let tree = interpreter::parse(code).expect("The program should parse");
+  let mut context = Context::new();
+  context.push_named_scope("outer".to_string());
+  interpreter::evaluate(&tree, &mut context).expect("The program should run");
+  context.get_scope_depth()
+}
+
+#[test]
+fn break_out_of_nested_blocks_pops_their_scopes() {
+  assert_eq!(
+    scope_depth_after("while (true) { if (true) { if (true) { break } } }"),
+    1
+  );
+  assert_eq!(
+    scope_depth_after("outer: while (true) { while (true) { if (true) { break outer } } }"),
+    1
+  );
+  assert_eq!(
+    scope_depth_after("i = 0\nwhile (i < 3) { i = i + 1\nif (true) { continue } }"),
+    1
+  );
+}
+
+#[test]
+fn return_out_of_nested_blocks_pops_their_scopes() {
+  assert_eq!(
+    scope_depth_after("fn f() { while (true) { if (true) { return 1 } } }\nf()"),
+    1
+  );
+}
+
+#[test]
+fn counting_loop_updates_its_counter_and_terminates() {
+  // The step limit turns a loop that never sees its counter change into a failure, not a hang
+  let tree =
+    interpreter::parse("i = 0\nwhile (i != 3) { i = i + 1 }\ni").expect("The program should parse");
+  let mut context = Context::new();
+  context.set_max_steps(Some(1000));
+  let value = interpreter::evaluate(&tree, &mut context).expect("The loop should terminate");
+  assert_eq!(value.to_string(), "3");
+}
+
+#[test]
+fn variable_first_assigned_in_a_loop_body_stays_in_it() {
+  assert_eq!(
+    error_message("i = 0\nwhile (i < 2) { i = i + 1\ninner = i }\ninner"),
+    "Attempted to access unset identifier: 'inner', at line 4, column 1"
+  );
+}
+
+#[test]
+fn boolean_literal_feeds_a_condition() {
+  assert_eq!(eval("x = 0\nif (true) { x = 1 } else { x = 2 }\nx"), "1");
+  assert_eq!(eval("x = 0\nif (false) { x = 1 } else { x = 2 }\nx"), "2");
+  assert_eq!(eval("flag = true\nx = 0\nif (flag) { x = 1 }\nx"), "1");
+  assert_eq!(eval("x = 0\nwhile (false) { x = 1 }\nx"), "0");
+}
@@ -0,0 +1,75 @@
+//! Tests of the locations errors are reported at, run as whole programs.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::eval_error;
+use interpreter::token::{Token, TokenType};
+use interpreter::{ASTree, Context, SourceError};
+
+/// Runs a program that is expected to fail with a single error, returning the error.
+fn single_error(code: &str) -> SourceError {
+  let errors: Vec<SourceError> = eval_error(code).get_errors().to_vec();
+  assert_eq!(errors.len(), 1, "{errors:?}");
+  errors[0].clone()
+}
+
+/// Returns the line and column of an error.
+fn location(error: &SourceError) -> (Option<usize>, Option<usize>) {
+  (error.get_line(), error.get_column())
+}
+
+#[test]
+fn runtime_error_is_located_at_the_failing_node() {
+  assert_eq!(
+    location(&single_error("x = 1\ny = x + \"a\"")),
+    (Some(2), Some(7))
+  );
+  assert_eq!(
+    location(&single_error("fn f(a) {\n  return a[3]\n}\nf([1])")),
+    (Some(2), Some(11))
+  );
+}
+
+#[test]
+fn runtime_error_location_ignores_locations_in_values() {
+  let error: SourceError = single_error("x = 1\nthrow \"line 9, column 9\"");
+  assert!(error.get_message().contains("line 9, column 9"));
+  assert_eq!(location(&error), (Some(2), Some(1)));
+}
+
+#[test]
+fn error_in_an_included_file_is_located_at_the_include() {
+  let path: PathBuf =
+    std::env::temp_dir().join(format!("{}_failing_include.txt", std::process::id()));
+  fs::write(&path, "x = 1\n\nthrow \"boom\"").expect("The file should be written");
+  let error: SourceError = single_error(&format!("y = 2\ninclude \"{}\"", path.display()));
+  fs::remove_file(&path).expect("The file should be removed");
+  assert_eq!(location(&error), (Some(2), Some(1)));
+}
+
+#[test]
+fn integer_literal_out_of_range_is_located_even_when_evaluated() {
+  let error = eval_error("x = 1\ny = 99999999999999999999");
+  assert_eq!(error.get_stage(), "lexing");
+  assert_eq!(location(&error.get_errors()[0]), (Some(2), Some(5)));
+
+  // A tree built without the lexer reports the literal when evaluating it
+  let literal: ASTree = ASTree::new(Token::new(
+    TokenType::NUMERIC,
+    "99999999999999999999".to_string(),
+    0,
+    3,
+    7,
+  ));
+  let message: String = literal
+    .eval(&mut Context::new())
+    .expect_err("The literal doesn't fit in an integer");
+  assert!(
+    message.starts_with("Invalid integer literal '99999999999999999999'"),
+    "{message}"
+  );
+  assert!(message.ends_with("at line 3, column 7"), "{message}");
+}
@@ -0,0 +1,111 @@
+//! Tests of function definitions, calls and function values, run as whole programs.
+
+mod common;
+
+use common::{error_message, eval};
+use interpreter::context::Context;
+
+#[test]
+fn global_assignment_in_a_function_outlives_the_call() {
+  assert_eq!(
+    eval("fn init() { global counter = 5 }\ninit()\ncounter"),
+    "5"
+  );
+  assert_eq!(
+    eval("counter = 1\nfn bump() { global counter = counter + 1 }\nbump()\nbump()\ncounter"),
+    "3"
+  );
+}
+
+#[test]
+fn function_passed_as_argument_is_called() {
+  assert_eq!(
+    eval("fn twice(f, x) { f(f(x)) }\nfn inc(n) { n + 1 }\ntwice(inc, 3)"),
+    "5"
+  );
+}
+
+#[test]
+fn function_returned_from_function_is_called() {
+  assert_eq!(
+    eval("fn double(x) { x * 2 }\nfn pick() { double }\nf = pick()\nf(21)"),
+    "42"
+  );
+  assert_eq!(
+    eval("fn double(x) { x * 2 }\nfn pick() { double }\npick()(4)"),
+    "8"
+  );
+}
+
+/// Runs a program with a custom call depth limit.
+fn eval_with_max_call_depth(code: &str, max_call_depth: usize) -> Result<String, String> {
+  let tree = interpreter::parse(code).expect("The program should parse");
+  let mut context = Context::new();
+  context.set_max_call_depth(max_call_depth);
+  interpreter::evaluate(&tree, &mut context)
+    .map(|value| value.to_string())
+    .map_err(|error| error.get_errors()[0].get_message().clone())
+}
+
+#[test]
+fn recursion_within_the_call_depth_limit_succeeds() {
+  let countdown = "fn down(n) { if (n == 0) { return 0 }\nreturn down(n - 1) + 1 }\n";
+  assert_eq!(
+    eval_with_max_call_depth(&format!("{countdown}down(49)"), 50),
+    Ok("49".to_string())
+  );
+  assert!(eval_with_max_call_depth(&format!("{countdown}down(50)"), 50).is_err());
+  assert_eq!(eval(&format!("{countdown}down(100)")), "100");
+}
+
+#[test]
+fn recursion_past_the_call_depth_limit_fails() {
+  let result = eval_with_max_call_depth("fn forever(n) { forever(n + 1) }\nforever(0)", 50);
+  assert!(
+    result
+      .as_ref()
+      .is_err_and(|message| message.starts_with("Maximum call depth of 50 exceeded")),
+    "{result:?}"
+  );
+}
+
+#[test]
+fn function_cannot_see_the_locals_of_its_caller() {
+  assert_eq!(
+    error_message("fn peek() { secret }\nfn caller() { secret = 1\npeek() }\ncaller()"),
+    "Attempted to access unset identifier: 'secret', at line 1, column 13"
+  );
+}
+
+#[test]
+fn function_sees_globals_and_its_parameters() {
+  assert_eq!(eval("base = 10\nfn add(x) { base + x }\nadd(5)"), "15");
+}
+
+/// Runs a program with profiling enabled, returning its value and the number of steps it took.
+fn eval_counting_steps(code: &str) -> (String, u64) {
+  let tree = interpreter::parse(code).expect("The program should parse");
+  let mut context = Context::new();
+  context.set_profiling(true);
+  let value = interpreter::evaluate(&tree, &mut context).expect("The program should run");
+  let steps: u64 = context
+    .get_profile()
+    .expect("Profiling is enabled")
+    .values()
+    .sum();
+  (value.to_string(), steps)
+}
+
+#[test]
+fn memoized_function_reuses_earlier_results() {
+  let fib = "fn fib(n) { if (n < 2) { return n }\nreturn fib(n - 1) + fib(n - 2) }\n";
+  let (plain, plain_steps) = eval_counting_steps(&format!("{fib}fib(12)"));
+  let (memoized, memoized_steps) =
+    eval_counting_steps(&format!("{fib}fib = memoize(fib)\nfib(12)"));
+  assert_eq!(plain, "144");
+  assert_eq!(memoized, "144");
+  assert!(
+    memoized_steps * 10 < plain_steps,
+    "{memoized_steps} steps memoized, {plain_steps} without"
+  );
+}
@@ -0,0 +1,89 @@
+//! Tests of including files and importing them as modules, run as whole programs.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::{error_message, eval};
+
+/// Writes a module file to the temporary directory, creating the directories it is in.
+///
+/// # Arguments
+///
+/// * `name` - The file name, made unique to this test process, which may start with directories,
+///   e.g. `dir/b.txt`.
+/// * `code` - The source code of the module.
+///
+/// # Returns
+///
+/// * `PathBuf` - The absolute path of the file.
+fn write_module(name: &str, code: &str) -> PathBuf {
+  let path: PathBuf = std::env::temp_dir().join(format!("{}_{}", std::process::id(), name));
+  fs::create_dir_all(
+    path
+      .parent()
+      .expect("The file is in the temporary directory"),
+  )
+  .expect("The directories should be created");
+  fs::write(&path, code).expect("The module should be written");
+  path
+}
+
+#[test]
+fn imported_module_functions_are_called_through_the_module() {
+  let path: PathBuf = write_module(
+    "math_module.txt",
+    "fn square(x) { x * x }\nfn area(w, h) { w * h }",
+  );
+  let result: String = eval(&format!(
+    "import \"{}\" as m\nm.square(4) + m.area(2, 3)",
+    path.display()
+  ));
+  fs::remove_file(&path).expect("The module should be removed");
+  assert_eq!(result, "22");
+}
+
+#[test]
+fn imported_functions_stay_out_of_the_global_scope() {
+  let path: PathBuf = write_module("hidden_module.txt", "fn square(x) { x * x }");
+  let message: String = error_message(&format!("import \"{}\" as m\nsquare(4)", path.display()));
+  fs::remove_file(&path).expect("The module should be removed");
+  assert!(
+    message.starts_with("Attempted to call unset function: 'square'"),
+    "{message}"
+  );
+}
+
+#[test]
+fn circular_include_is_an_error() {
+  let pid: u32 = std::process::id();
+  let a: PathBuf = write_module("cycle_a.txt", &format!("include \"{pid}_cycle_b.txt\""));
+  let b: PathBuf = write_module("cycle_b.txt", &format!("include \"{pid}_cycle_a.txt\""));
+  let message: String = error_message(&format!("include \"{}\"", a.display()));
+  fs::remove_file(&a).expect("The module should be removed");
+  fs::remove_file(&b).expect("The module should be removed");
+  let canonical_a: PathBuf = fs::canonicalize(std::env::temp_dir())
+    .expect("The temporary directory exists")
+    .join(format!("{pid}_cycle_a.txt"));
+  assert!(
+    message.starts_with(&format!(
+      "Circular include or import of '{}'",
+      canonical_a.display()
+    )),
+    "{message}"
+  );
+}
+
+#[test]
+fn include_is_resolved_relative_to_the_including_file() {
+  // `sub/b.txt` includes `c.txt` next to itself, not in the directory of the program
+  let c: PathBuf = write_module("nested/sub/c.txt", "from_c = 3");
+  let b: PathBuf = write_module("nested/sub/b.txt", "include \"c.txt\"\nfrom_b = from_c * 2");
+  let a: PathBuf = write_module("nested/a.txt", "include \"sub/b.txt\"");
+  let result: String = eval(&format!("include \"{}\"\nfrom_b + from_c", a.display()));
+  fs::remove_dir_all(a.parent().expect("The file is in a directory"))
+    .expect("The modules should be removed");
+  assert!(!c.exists() && !b.exists());
+  assert_eq!(result, "9");
+}
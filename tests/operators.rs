@@ -0,0 +1,103 @@
+//! Tests of operators and their precedence, run as whole programs.
+
+mod common;
+
+use common::{error_message, eval};
+
+#[test]
+fn integers_and_floats_are_ordered_by_value() {
+  assert_eq!(eval("1 < 1.5"), "true");
+  assert_eq!(eval("2.5 >= 2"), "true");
+  assert_eq!(eval("1 <= 1.0"), "true");
+  assert_eq!(eval("3 > 3.5"), "false");
+}
+
+#[test]
+fn numbers_and_strings_cannot_be_ordered() {
+  assert!(error_message("1 < \"a\"").starts_with("Type mismatch for binary operation <"));
+  assert!(error_message("1.5 > \"a\"").starts_with("Type mismatch for binary operation >"));
+}
+
+#[test]
+fn plus_concatenates_arrays() {
+  assert_eq!(eval("[1, 2] + [3] == [1, 2, 3]"), "true");
+  assert_eq!(eval("[1, 2] + []"), "[1, 2]");
+  assert_eq!(eval("concat([1], [2, 3], [])"), "[1, 2, 3]");
+}
+
+#[test]
+fn plus_between_array_and_other_value_is_an_error() {
+  assert!(error_message("[1] + 2").starts_with("Type mismatch for binary operation +"));
+}
+
+#[test]
+fn precedence_tiers_group_mixed_expressions() {
+  // `%` above `==`, `==` above `&&`, comparisons above `&&`
+  assert_eq!(eval("i = 4\ni % 2 == 0 && i < 10"), "true");
+  assert_eq!(eval("i = 12\ni % 2 == 0 && i < 10"), "false");
+  assert_eq!(eval("1 + 2 * 3 == 7"), "true");
+  assert_eq!(eval("2 + 3 % 2"), "3");
+  // Comparisons bind tighter than equality
+  assert_eq!(eval("1 < 2 == 3 < 4"), "true");
+  // Same-tier operators are left-associative
+  assert_eq!(eval("10 - 4 - 3"), "3");
+  assert_eq!(eval("7 % 3 * 2"), "2");
+  assert_eq!(eval("24 / 4 / 2"), "3");
+}
+
+#[test]
+fn logical_operators_bind_looser_than_equality() {
+  assert_eq!(eval("1 == 1 && 2 == 2"), "true");
+  assert_eq!(eval("1 == 2 || 3 == 3"), "true");
+  // `&&` binds tighter than `||`
+  assert_eq!(eval("1 == 1 || 1 == 2 && 1 == 2"), "true");
+}
+
+#[test]
+fn compound_conditions_need_no_parentheses() {
+  assert_eq!(eval("i = 3\nj = 1\ni < 10 && j > 0"), "true");
+  assert_eq!(eval("i = 3\nj = 0\ni < 10 && j > 0"), "false");
+  assert_eq!(eval("i = 12\nj = 1\ni < 10 || j >= 1"), "true");
+  assert_eq!(
+    eval("i = 0\nj = 5\nwhile (i < 10 && j > 0) { i = i + 1\nj = j - 1 }\ni"),
+    "5"
+  );
+}
+
+#[cfg(not(feature = "int64"))]
+#[test]
+fn integers_wrap_around_at_the_32_bit_boundary() {
+  assert_eq!(eval("2147483647 + 1"), "-2147483648");
+  assert_eq!(eval("-2147483647 - 2"), "2147483647");
+  assert_eq!(eval("65536 * 65536"), "0");
+  assert_eq!(eval("2147483646 + 1"), "2147483647");
+}
+
+#[cfg(feature = "int64")]
+#[test]
+fn integers_wrap_around_at_the_64_bit_boundary() {
+  assert_eq!(eval("2147483647 + 1"), "2147483648");
+  assert_eq!(eval("9223372036854775807 + 1"), "-9223372036854775808");
+  assert_eq!(eval("4294967296 * 4294967296"), "0");
+}
+
+#[test]
+fn unary_operators_bind_tighter_than_binary_ones() {
+  // `!a == b` is `(!a) == b`, not `!(a == b)`
+  assert_eq!(eval("a = false\nb = true\n!a == b"), "true");
+  assert_eq!(eval("a = true\nb = true\n!a == b"), "false");
+  assert_eq!(eval("a = true\nb = true\n!(a == b)"), "false");
+  // `-a * b` is `(-a) * b`, and `-a - b` is `(-a) - b`
+  assert_eq!(eval("a = 2\nb = 3\nc = -a * b\nc"), "-6");
+  assert_eq!(eval("a = 2\nb = 3\nc = -a - b\nc"), "-5");
+  assert_eq!(eval("3 - -2"), "5");
+}
+
+#[test]
+fn not_binds_looser_than_comparisons() {
+  // `not a == b` is `not (a == b)`
+  assert_eq!(eval("a = true\nb = true\nnot a == b"), "false");
+  assert_eq!(eval("not 1 < 2"), "false");
+  // but tighter than `&&`
+  assert_eq!(eval("not false && false"), "false");
+}
@@ -0,0 +1,62 @@
+//! Tests of syntactic sugar and literals, run as whole programs.
+
+mod common;
+
+use common::{eval, eval_error};
+
+#[test]
+fn method_call_passes_the_receiver_as_first_argument() {
+  assert_eq!(eval("\"abc\".len()"), eval("len(\"abc\")"));
+  assert_eq!(eval("\"abc\".len()"), "3");
+  assert_eq!(eval("a = [1]\na.push(2)\na"), "[1, 2]");
+  assert_eq!(eval("\"haystack\".contains(\"st\")"), "true");
+}
+
+#[test]
+fn raw_string_keeps_backslashes() {
+  assert_eq!(eval("r\"a\\nb\""), "a\\nb");
+  assert_eq!(eval("len(r\"a\\nb\")"), "4");
+  assert_eq!(eval("r\"C:\\dir\\\""), "C:\\dir\\");
+}
+
+#[test]
+fn postfix_operations_chain_left_to_right() {
+  assert_eq!(eval("fn getArray() { [10, 20, 30] }\ngetArray()[1]"), "20");
+  assert_eq!(eval("m = [[1, [2, 3]]]\nm[0][1][0]"), "2");
+  assert_eq!(
+    eval("fn double(x) { x * 2 }\nfns = [double]\nfns[0](21)"),
+    "42"
+  );
+  assert_eq!(
+    eval("fn words() { [\"ab\", \"cde\"] }\nwords()[1].len()"),
+    "3"
+  );
+}
+
+#[test]
+fn string_escapes_are_decoded() {
+  assert_eq!(eval("\"a\\\"b\""), "a\"b");
+  assert_eq!(eval("len(\"a\\\"b\")"), "3");
+  assert_eq!(eval("\"line1\\nline2\""), "line1\nline2");
+  assert_eq!(eval("\"tab\\there\""), "tab\there");
+  assert_eq!(eval("len(\"a\\\\b\")"), "3");
+}
+
+#[test]
+fn unknown_escape_is_a_lexing_error() {
+  let error = eval_error("\"a\\q\"");
+  assert_eq!(error.get_stage(), "lexing");
+  assert_eq!(
+    error.get_errors()[0].get_message(),
+    "Unknown escape sequence '\\q' at line 1, column 3"
+  );
+}
+
+#[test]
+fn multi_byte_string_literal_survives_a_round_trip() {
+  assert_eq!(eval("\"café\""), "café");
+  assert_eq!(eval("len(\"café\")"), "4");
+  assert_eq!(eval("s = \"日本語 🦀\"\ns"), "日本語 🦀");
+  assert_eq!(eval("len(\"日本語 🦀\")"), "5");
+  assert_eq!(eval("naïve = \"ü\"\nnaïve + \"ß\""), "üß");
+}
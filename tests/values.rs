@@ -0,0 +1,95 @@
+//! Tests of runtime values and how they are displayed.
+
+mod common;
+
+use common::eval;
+use interpreter::ast::RuntimeValue;
+
+#[test]
+fn integer_is_written_in_decimal_hex_and_binary() {
+  let value: RuntimeValue = RuntimeValue::INTEGER(173);
+  assert_eq!(value.to_string(), "173");
+  assert_eq!(value.format_radix(10), "173");
+  assert_eq!(value.format_radix(16), "ad");
+  assert_eq!(value.format_radix(2), "10101101");
+  assert_eq!(RuntimeValue::INTEGER(-173).format_radix(16), "-ad");
+
+  assert_eq!(eval("to_hex(173)"), "ad");
+  assert_eq!(eval("to_bin(173)"), "10101101");
+  assert_eq!(eval("173"), "173");
+}
+
+#[test]
+fn radix_applies_to_integers_nested_in_arrays() {
+  let value: RuntimeValue = interpreter::run("[255, 1.5, \"ff\"]").unwrap();
+  assert_eq!(value.format_radix(16), "[ff, 1.5, \"ff\"]");
+  assert_eq!(value.to_string(), "[255, 1.5, \"ff\"]");
+}
+
+#[test]
+fn floats_always_show_a_decimal_point() {
+  assert_eq!(eval("1.0"), "1.0");
+  assert_eq!(eval("0.1"), "0.1");
+  assert_eq!(eval("2.5 * 2.0"), "5.0");
+  // The lexer has no exponent syntax, 1e10 is computed
+  assert_eq!(eval("100000.0 * 100000.0"), "10000000000.0");
+  assert_eq!(eval("parse_float(\"1e10\")"), "10000000000.0");
+  assert_eq!(RuntimeValue::FLOAT(1e10).to_string(), "10000000000.0");
+}
+
+#[test]
+fn pprint_shows_floats_with_a_decimal_point() {
+  // `pprint` prints the value's `format_pretty`
+  for (value, expected) in [(1.0, "1.0"), (0.1, "0.1"), (1e10, "10000000000.0")] {
+    assert_eq!(RuntimeValue::FLOAT(value).format_pretty(), expected);
+  }
+  let value: RuntimeValue = interpreter::run("[1.0, 0.1, 100000.0 * 100000.0]").unwrap();
+  assert_eq!(
+    value.format_pretty(),
+    "[\n  1.0,\n  0.1,\n  10000000000.0\n]"
+  );
+}
+
+#[test]
+fn array_containing_itself_is_displayed_without_recursing() {
+  assert_eq!(eval("a = [1]\npush(a, a)\na"), "[1, [...]]");
+  assert_eq!(eval("a = [1]\npush(a, a)\njoin([a], \"\")"), "[1, [...]]");
+  let value: RuntimeValue = interpreter::run("a = [1]\npush(a, a)\na").unwrap();
+  assert_eq!(
+    format!("{value:?}"),
+    "ARRAY(RefCell { value: [INTEGER(1), ARRAY([...])] })"
+  );
+}
+
+#[test]
+fn arrays_in_a_cycle_can_be_compared_and_copied() {
+  let cycle = "a = [1]\npush(a, a)\n";
+  assert_eq!(eval(&format!("{cycle}a == a")), "true");
+  assert_eq!(eval(&format!("{cycle}a == clone(a)")), "true");
+  assert_eq!(
+    eval(&format!("{cycle}b = clone(a)\npush(b, 2)\na == b")),
+    "false"
+  );
+  // The copy contains itself, not the original
+  assert_eq!(
+    eval(&format!("{cycle}b = clone(a)\npush(b, 2)\nb[1] == b")),
+    "true"
+  );
+  assert_eq!(eval("a = [1]\nb = [a]\npush(a, b)\na == clone(a)"), "true");
+}
+
+#[test]
+fn floats_are_never_displayed_with_an_exponent() {
+  assert_eq!(eval("parse_float(\"1e16\")"), "10000000000000000.0");
+  assert_eq!(eval("parse_float(\"1e-7\")"), "0.0000001");
+  assert_eq!(eval("parse_float(\"-2.5e20\")"), "-250000000000000000000.0");
+  assert_eq!(eval("[parse_float(\"1e16\")]"), "[10000000000000000.0]");
+}
+
+#[test]
+fn non_finite_floats_are_displayed_by_name() {
+  assert_eq!(eval("parse_float(\"inf\")"), "inf");
+  assert_eq!(eval("parse_float(\"-inf\")"), "-inf");
+  assert_eq!(eval("parse_float(\"NaN\")"), "nan");
+  assert_eq!(RuntimeValue::FLOAT(-0.0).to_string(), "-0.0");
+}
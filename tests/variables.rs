@@ -0,0 +1,60 @@
+//! Tests of variables, constants and scoping, run as whole programs.
+
+mod common;
+
+use common::{error_message, eval, eval_error};
+use interpreter::{InterpretError, SourceError};
+
+#[test]
+fn reassigning_a_constant_is_an_error_at_the_assignment() {
+  let error: InterpretError = eval_error("const x = 1\nx = 2");
+  assert_eq!(error.get_stage(), "runtime");
+  let error: &SourceError = &error.get_errors()[0];
+  assert_eq!(
+    error.get_message(),
+    "Cannot reassign constant 'x', at line 2, column 3"
+  );
+  assert_eq!(error.get_line(), Some(2));
+  assert_eq!(error.get_column(), Some(3));
+}
+
+#[test]
+fn constant_can_be_read() {
+  assert_eq!(eval("const PI = 3\nPI * 2"), "6");
+}
+
+#[test]
+fn destructuring_assigns_each_element() {
+  assert_eq!(eval("a, b = [1, 2]\na * 10 + b"), "12");
+  assert_eq!(eval("a, b = [1, 2]\na, b = [b, a]\na * 10 + b"), "21");
+  assert_eq!(
+    eval("fn pair() { [\"x\", 3] }\nname, n = pair()\nname"),
+    "x"
+  );
+}
+
+#[test]
+fn destructuring_a_mismatched_value_is_an_error() {
+  assert_eq!(
+    error_message("a, b = [1, 2, 3]"),
+    "Cannot destructure an array of 3 element(s) into 2 variable(s), at line 1, column 6"
+  );
+  assert_eq!(
+    error_message("a, b, c = [1]"),
+    "Cannot destructure an array of 1 element(s) into 3 variable(s), at line 1, column 9"
+  );
+  assert_eq!(
+    error_message("a, b = 5"),
+    "Cannot destructure INTEGER(5), expected an array, at line 1, column 6"
+  );
+}
+
+#[test]
+fn boolean_literals_cannot_be_assigned_to() {
+  assert_eq!(eval_error("true = 1").get_stage(), "parsing");
+  assert_eq!(eval_error("x = 1\nfalse = x").get_stage(), "parsing");
+  assert_eq!(
+    error_message("fn true() {}"),
+    "Expected IDENTIFIER before line 1, column 4, found BOOL"
+  );
+}